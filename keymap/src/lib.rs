@@ -0,0 +1,324 @@
+//! Rebindable controls shared by the games. Each game names the actions a player can take,
+//! and an [`ActionMap`] says which keys, gamepad buttons, sticks and swipes trigger them.
+
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+mod rebind;
+
+pub use rebind::{
+    binding_label, BindingsLabel, RebindPlugin, RebindSet, Rebindable, Rebinding, CANCEL_KEY,
+};
+
+/// How far a stick has to be pushed before it counts as pressed.
+pub const STICK_DEAD_ZONE: f32 = 0.5;
+/// Logical pixels a finger has to move to count as a swipe.
+pub const SWIPE_DISTANCE: f32 = 30.0;
+
+/// A key, button, stick or gesture that can trigger an action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    GamepadButton(GamepadButtonType),
+    /// Pushing a stick along an axis, towards its positive end if `true`.
+    GamepadAxis(GamepadAxisType, bool),
+    Swipe(Swipe),
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => {
+                let key = format!("{:?}", key);
+                let key = key
+                    .strip_prefix("Key")
+                    .or_else(|| key.strip_prefix("Digit"))
+                    .unwrap_or(&key);
+                write!(f, "{}", key)
+            }
+            Binding::GamepadButton(button) => write!(f, "Pad {:?}", button),
+            Binding::GamepadAxis(axis, true) => write!(f, "{:?}+", axis),
+            Binding::GamepadAxis(axis, false) => write!(f, "{:?}-", axis),
+            Binding::Swipe(swipe) => write!(f, "Swipe {:?}", swipe),
+        }
+    }
+}
+
+/// Direction of a swipe across the touch screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Swipe {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// The bindings of one player. Any number of bindings can trigger the same action.
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ActionMap<A>(Vec<(Binding, A)>);
+
+impl<A> Default for ActionMap<A> {
+    fn default() -> Self {
+        Self(vec![])
+    }
+}
+
+impl<A: Copy + PartialEq> ActionMap<A> {
+    /// Makes the binding trigger the action, instead of whatever it triggered before.
+    pub fn bind(&mut self, binding: Binding, action: A) -> &mut Self {
+        self.unbind(binding);
+        self.0.push((binding, action));
+        self
+    }
+
+    pub fn unbind(&mut self, binding: Binding) -> &mut Self {
+        self.0.retain(|&(bound, _)| bound != binding);
+        self
+    }
+
+    /// Removes every binding of the action.
+    pub fn clear(&mut self, action: A) -> &mut Self {
+        self.0.retain(|&(_, bound)| bound != action);
+        self
+    }
+
+    /// Adds the other map's bindings, except those this map already uses.
+    pub fn merge(&mut self, other: &Self) -> &mut Self {
+        for &(binding, action) in &other.0 {
+            if self.action(binding).is_none() {
+                self.0.push((binding, action));
+            }
+        }
+        self
+    }
+
+    /// Everything that triggers the action.
+    pub fn bindings(&self, action: A) -> impl Iterator<Item = Binding> + '_ {
+        self.0
+            .iter()
+            .filter(move |&&(_, bound)| bound == action)
+            .map(|&(binding, _)| binding)
+    }
+
+    pub fn action(&self, binding: Binding) -> Option<A> {
+        self.0
+            .iter()
+            .find(|&&(bound, _)| bound == binding)
+            .map(|&(_, action)| action)
+    }
+
+    fn sticks(&self) -> impl Iterator<Item = (GamepadAxisType, bool)> + '_ {
+        self.0.iter().filter_map(|&(binding, _)| match binding {
+            Binding::GamepadAxis(axis_type, positive) => Some((axis_type, positive)),
+            _ => None,
+        })
+    }
+}
+
+/// The keyboard and gamepad state that bindings are checked against.
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl ActionInput<'_> {
+    /// Connected gamepads, oldest first.
+    pub fn gamepads(&self) -> Vec<Gamepad> {
+        let mut gamepads = self.gamepads.iter().collect::<Vec<_>>();
+        gamepads.sort_by_key(|gamepad| gamepad.id);
+        gamepads
+    }
+
+    /// Whether the action is held down on the keyboard or any of the given gamepads.
+    pub fn pressed<A: Copy + PartialEq>(
+        &self,
+        action_map: &ActionMap<A>,
+        action: A,
+        gamepads: &[Gamepad],
+    ) -> bool {
+        action_map.bindings(action).any(|binding| match binding {
+            Binding::Key(key) => self.keys.pressed(key),
+            Binding::GamepadButton(button_type) => gamepads.iter().any(|&gamepad| {
+                self.gamepad_buttons
+                    .pressed(GamepadButton::new(gamepad, button_type))
+            }),
+            Binding::GamepadAxis(axis_type, positive) => {
+                self.is_pushed(axis_type, positive, gamepads)
+            }
+            // A swipe is over as soon as it happens.
+            Binding::Swipe(_) => false,
+        })
+    }
+
+    /// Keys, and buttons of the given gamepads, first pressed this frame.
+    pub fn just_pressed<'a>(
+        &'a self,
+        gamepads: &'a [Gamepad],
+    ) -> impl Iterator<Item = Binding> + 'a {
+        let keys = self.keys.get_just_pressed().map(|&key| Binding::Key(key));
+        let buttons = self
+            .gamepad_buttons
+            .get_just_pressed()
+            .filter(|button| gamepads.contains(&button.gamepad))
+            .map(|button| Binding::GamepadButton(button.button_type));
+
+        keys.chain(buttons)
+    }
+
+    fn is_pushed(&self, axis_type: GamepadAxisType, positive: bool, gamepads: &[Gamepad]) -> bool {
+        gamepads.iter().any(|&gamepad| {
+            let value = self
+                .gamepad_axes
+                .get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or(0.0);
+            match positive {
+                true => value > STICK_DEAD_ZONE,
+                false => value < -STICK_DEAD_ZONE,
+            }
+        })
+    }
+}
+
+/// Stick directions a player is holding past the dead zone, so a push only triggers once.
+#[derive(Component, Default)]
+pub struct HeldSticks(Vec<(GamepadAxisType, bool)>);
+
+impl HeldSticks {
+    /// Stick bindings of the map pushed past the dead zone since last frame.
+    pub fn just_pushed<A: Copy + PartialEq>(
+        &mut self,
+        input: &ActionInput,
+        action_map: &ActionMap<A>,
+        gamepads: &[Gamepad],
+    ) -> Vec<Binding> {
+        let pushed = action_map
+            .sticks()
+            .filter(|&(axis_type, positive)| input.is_pushed(axis_type, positive, gamepads))
+            .collect::<Vec<_>>();
+        let just_pushed = pushed
+            .iter()
+            .filter(|stick| !self.0.contains(stick))
+            .map(|&(axis_type, positive)| Binding::GamepadAxis(axis_type, positive))
+            .collect();
+
+        self.0 = pushed;
+        just_pushed
+    }
+}
+
+/// Turns finger movement into swipes. A long swipe can count more than once, so a finger
+/// can steer without being lifted.
+#[derive(Default)]
+pub struct SwipeTracker {
+    /// Where each finger's current swipe started.
+    anchors: HashMap<u64, Vec2>,
+}
+
+impl SwipeTracker {
+    /// Swipes made since last frame.
+    pub fn swipes(&mut self, touches: &Touches) -> Vec<Swipe> {
+        for touch in touches
+            .iter_just_released()
+            .chain(touches.iter_just_canceled())
+        {
+            self.anchors.remove(&touch.id());
+        }
+
+        touches
+            .iter()
+            .filter_map(|touch| {
+                let anchor = self
+                    .anchors
+                    .entry(touch.id())
+                    .or_insert(touch.start_position());
+                let delta = touch.position() - *anchor;
+                if delta.length() < SWIPE_DISTANCE {
+                    return None;
+                }
+
+                *anchor = touch.position();
+                // Screen coordinates grow downwards.
+                Some(match delta.x.abs() > delta.y.abs() {
+                    true if delta.x > 0.0 => Swipe::Right,
+                    true => Swipe::Left,
+                    false if delta.y > 0.0 => Swipe::Down,
+                    false => Swipe::Up,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Reads a keymap saved with [`save`].
+pub fn load<T: DeserializeOwned>(path: impl AsRef<Path>) -> io::Result<T> {
+    ron::from_str(&fs::read_to_string(path)?)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+pub fn save<T: Serialize>(keymap: &T, path: impl AsRef<Path>) -> io::Result<()> {
+    let keymap = ron::ser::to_string_pretty(keymap, default())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+    fs::write(path, keymap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebinding_moves_a_binding_to_the_new_action() {
+        let mut action_map = ActionMap::default();
+        action_map
+            .bind(Binding::Key(KeyCode::KeyW), "up")
+            .bind(Binding::Key(KeyCode::ArrowUp), "up");
+
+        // AZERTY players move up with Z.
+        action_map
+            .unbind(Binding::Key(KeyCode::KeyW))
+            .bind(Binding::Key(KeyCode::KeyZ), "up")
+            .bind(Binding::Key(KeyCode::ArrowUp), "down");
+
+        assert_eq!(action_map.action(Binding::Key(KeyCode::KeyZ)), Some("up"));
+        assert_eq!(action_map.action(Binding::Key(KeyCode::KeyW)), None);
+        assert_eq!(
+            action_map.action(Binding::Key(KeyCode::ArrowUp)),
+            Some("down")
+        );
+        assert_eq!(
+            action_map.bindings("up").collect::<Vec<_>>(),
+            [Binding::Key(KeyCode::KeyZ)]
+        );
+    }
+
+    #[test]
+    fn labels_every_binding_of_an_action() {
+        let mut action_map = ActionMap::default();
+        action_map.bind(Binding::Key(KeyCode::KeyW), "up").bind(
+            Binding::GamepadAxis(GamepadAxisType::LeftStickY, true),
+            "up",
+        );
+
+        assert_eq!(binding_label(&action_map, "up"), "W, LeftStickY+");
+        assert_eq!(binding_label(&action_map, "down"), "Unbound");
+    }
+
+    #[test]
+    fn round_trips_through_ron() {
+        let mut action_map = ActionMap::default();
+        action_map
+            .bind(Binding::Key(KeyCode::Space), 0)
+            .bind(Binding::GamepadButton(GamepadButtonType::South), 0)
+            .bind(Binding::GamepadAxis(GamepadAxisType::LeftStickX, false), 1)
+            .bind(Binding::Swipe(Swipe::Up), 2);
+
+        let saved = ron::to_string(&action_map).unwrap();
+        assert_eq!(ron::from_str::<ActionMap<u8>>(&saved).unwrap(), action_map);
+    }
+}
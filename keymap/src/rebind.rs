@@ -0,0 +1,132 @@
+//! Rebinding actions from a game's controls screen. The game spawns the screen and its buttons,
+//! inserts a [`Rebinding`] when one is clicked, and [`RebindPlugin`] binds the next key or
+//! gamepad button pressed and keeps every [`BindingsLabel`] up to date.
+
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use crate::{ActionInput, ActionMap, Binding};
+
+/// Cancels a rebind instead of being bound.
+pub const CANCEL_KEY: KeyCode = KeyCode::Escape;
+
+/// A game's keymap resource, which holds the bindings of every player and saves them.
+pub trait Rebindable: Resource {
+    type Action: Copy + PartialEq + Send + Sync + 'static;
+
+    /// The player's bindings, or `None` if the player has none.
+    fn bindings(&self, player: usize) -> Option<&ActionMap<Self::Action>>;
+
+    /// The player's bindings to change, or `None` if the player cannot be rebound.
+    fn bindings_mut(&mut self, player: usize) -> Option<&mut ActionMap<Self::Action>>;
+
+    /// Persists the bindings. Only called after they changed.
+    fn save(&self);
+}
+
+/// The action waiting for a new binding, after its rebind button was clicked.
+#[derive(Resource, Clone, Copy, PartialEq, Eq)]
+pub struct Rebinding<A: Send + Sync + 'static> {
+    pub player: usize,
+    pub action: A,
+}
+
+/// A text showing the bindings of a player's action.
+#[derive(Component, Clone, Copy)]
+pub struct BindingsLabel<A: Send + Sync + 'static> {
+    pub player: usize,
+    pub action: A,
+}
+
+/// Systems that capture bindings and update labels, for a game to order its own systems against.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RebindSet;
+
+/// Rebinds the actions of the keymap `K` while the game is in `state`.
+pub struct RebindPlugin<K, S> {
+    state: S,
+    keymap: PhantomData<fn() -> K>,
+}
+
+impl<K, S> RebindPlugin<K, S> {
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            keymap: PhantomData,
+        }
+    }
+}
+
+impl<K: Rebindable, S: States> Plugin for RebindPlugin<K, S> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (capture_binding::<K>, update_bindings_labels::<K>)
+                .chain()
+                .in_set(RebindSet)
+                .run_if(in_state(self.state.clone())),
+        )
+        .add_systems(OnExit(self.state.clone()), |mut commands: Commands| {
+            commands.remove_resource::<Rebinding<K::Action>>();
+        });
+    }
+}
+
+/// The action's bindings as shown on a controls screen, like `W, ArrowUp`.
+pub fn binding_label<A: Copy + PartialEq>(action_map: &ActionMap<A>, action: A) -> String {
+    let bindings = action_map
+        .bindings(action)
+        .map(|binding| binding.to_string())
+        .collect::<Vec<_>>();
+
+    match bindings.is_empty() {
+        true => "Unbound".to_string(),
+        false => bindings.join(", "),
+    }
+}
+
+/// Binds the first key or gamepad button pressed while an action is waiting for one.
+fn capture_binding<K: Rebindable>(
+    mut commands: Commands,
+    rebinding: Option<Res<Rebinding<K::Action>>>,
+    input: ActionInput,
+    mut keymap: ResMut<K>,
+) {
+    let Some(rebinding) = rebinding else {
+        return;
+    };
+    let Some(binding) = input.just_pressed(&input.gamepads()).next() else {
+        return;
+    };
+
+    commands.remove_resource::<Rebinding<K::Action>>();
+    if binding == Binding::Key(CANCEL_KEY) {
+        return;
+    }
+
+    if let Some(action_map) = keymap.bindings_mut(rebinding.player) {
+        action_map.bind(binding, rebinding.action);
+        keymap.save();
+    }
+}
+
+fn update_bindings_labels<K: Rebindable>(
+    mut labels: Query<(&BindingsLabel<K::Action>, &mut Text)>,
+    keymap: Res<K>,
+    rebinding: Option<Res<Rebinding<K::Action>>>,
+) {
+    for (label, mut text) in labels.iter_mut() {
+        let is_rebinding = rebinding.as_deref().is_some_and(|rebinding| {
+            rebinding.player == label.player && rebinding.action == label.action
+        });
+
+        text.sections[0].value = match is_rebinding {
+            true => "Press a key...".to_string(),
+            false => keymap
+                .bindings(label.player)
+                .map(|action_map| binding_label(action_map, label.action))
+                .unwrap_or_else(|| binding_label(&ActionMap::default(), label.action)),
+        };
+    }
+}
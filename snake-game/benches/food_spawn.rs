@@ -0,0 +1,39 @@
+//! Plays ticks on a 200x200 board with a long snake, where food has to find a free cell
+//! every tick.
+//!
+//! ```sh
+//! cargo bench --bench food_spawn
+//! ```
+
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use snake_game::{config::SnakeConfig, rng::GameRng, sim::SnakeSim};
+
+const TICKS: usize = 100;
+
+fn large_board(c: &mut Criterion) {
+    let config = SnakeConfig {
+        board_size: (200, 200),
+        start_length: 100,
+        max_food: 1000,
+        food_interval: 0.0,
+        ..default()
+    };
+
+    c.bench_function("200x200 board, 100 ticks", |b| {
+        b.iter_batched(
+            || SnakeSim::new(&config, GameRng::from_seed(0)),
+            |mut sim| {
+                for _ in 0..TICKS {
+                    sim.step_queued();
+                }
+                sim
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, large_board);
+criterion_main!(benches);
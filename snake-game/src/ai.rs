@@ -0,0 +1,402 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use bevy::prelude::*;
+
+use crate::{
+    food::Food,
+    sim::{BoundaryMode, SimSnake, SnakeSim},
+    snake::components::Dir,
+};
+
+const DIRS: [Dir; 4] = [Dir::N, Dir::E, Dir::S, Dir::W];
+
+/// Steers a snake by picking a direction every tick.
+pub trait SnakeController: Send + Sync {
+    /// The direction to turn in before the next tick, or `None` to keep going straight.
+    fn next_dir(&mut self, view: &BoardView) -> Option<Dir>;
+}
+
+/// Who steers a player's snake.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControllerKind {
+    #[default]
+    Keyboard,
+    Greedy,
+    Bfs,
+    Hamiltonian,
+}
+
+impl ControllerKind {
+    pub const ALL: [Self; 4] = [Self::Keyboard, Self::Greedy, Self::Bfs, Self::Hamiltonian];
+
+    /// A controller for this kind, or `None` when a person is playing.
+    pub fn build(self) -> Option<Box<dyn SnakeController>> {
+        match self {
+            ControllerKind::Keyboard => None,
+            ControllerKind::Greedy => Some(Box::new(Greedy)),
+            ControllerKind::Bfs => Some(Box::new(BfsToFood)),
+            ControllerKind::Hamiltonian => Some(Box::<Hamiltonian>::default()),
+        }
+    }
+}
+
+/// What a controller gets to see of the game: everything, but only to read.
+pub struct BoardView<'a> {
+    sim: &'a SnakeSim,
+    player: usize,
+}
+
+impl<'a> BoardView<'a> {
+    pub fn new(sim: &'a SnakeSim, player: usize) -> Self {
+        Self { sim, player }
+    }
+
+    pub fn board_size(&self) -> Vec2 {
+        self.sim.board_size()
+    }
+
+    pub fn boundary_mode(&self) -> BoundaryMode {
+        self.sim.config().boundary_mode
+    }
+
+    /// The snake being steered.
+    pub fn snake(&self) -> &SimSnake {
+        self.sim.snake(self.player)
+    }
+
+    pub fn snakes(&self) -> &[SimSnake] {
+        self.sim.snakes()
+    }
+
+    pub fn food(&self) -> &[Food] {
+        self.sim.food()
+    }
+
+    pub fn walls(&self) -> &[Vec2] {
+        self.sim.walls()
+    }
+
+    /// The cell reached by moving one step from `coords`, or `None` if it is off a solid board.
+    pub fn neighbor(&self, coords: Vec2, dir: Dir) -> Option<Vec2> {
+        let coords = coords + Vec2::from(dir);
+
+        match self.boundary_mode() {
+            BoundaryMode::Wrap => Some(coords.rem_euclid(self.board_size())),
+            BoundaryMode::Solid => (coords.cmpge(Vec2::ZERO).all()
+                && coords.cmplt(self.board_size()).all())
+            .then_some(coords),
+        }
+    }
+
+    /// Whether moving onto the cell next tick is safe. Tails that are about to move away count as free.
+    pub fn is_free(&self, coords: Vec2) -> bool {
+        let grid = self.sim.occupancy();
+        if !grid.contains(coords) || grid.is_wall(coords) {
+            return false;
+        }
+
+        let segments = (0..self.snakes().len())
+            .map(|player| grid.segments(coords, player))
+            .sum::<u16>();
+
+        match segments {
+            0 => true,
+            1 => self.snakes().iter().any(|snake| {
+                let tail = snake.tail();
+                tail.coords == coords && !tail.is_bloated && snake.is_alive()
+            }),
+            _ => false,
+        }
+    }
+
+    /// Directions the snake can turn in next tick without hitting anything, straight ahead first.
+    ///
+    /// Cells another head can also move onto are only offered when there is nothing else, as
+    /// both snakes die if they meet there.
+    pub fn safe_dirs(&self) -> Vec<Dir> {
+        let head = self.snake().head();
+        let heading = head.direction.1;
+
+        let (contested, uncontested): (Vec<Dir>, Vec<Dir>) = std::iter::once(heading)
+            .chain(DIRS.into_iter().filter(|dir| !dir.is_parallel(heading)))
+            .filter(|&dir| {
+                self.neighbor(head.coords, dir)
+                    .is_some_and(|coords| self.is_free(coords))
+            })
+            .partition(|&dir| {
+                self.neighbor(head.coords, dir)
+                    .is_some_and(|coords| self.is_contested(coords))
+            });
+
+        if uncontested.is_empty() {
+            contested
+        } else {
+            uncontested
+        }
+    }
+
+    /// Whether another snake's head can also move onto the cell next tick.
+    fn is_contested(&self, coords: Vec2) -> bool {
+        self.snakes()
+            .iter()
+            .enumerate()
+            .filter(|&(player, snake)| player != self.player && snake.is_alive())
+            .any(|(_, snake)| {
+                DIRS.into_iter()
+                    .any(|dir| self.neighbor(snake.head().coords, dir) == Some(coords))
+            })
+    }
+
+    /// Number of steps between two cells, ignoring obstacles.
+    pub fn distance(&self, from: Vec2, to: Vec2) -> f32 {
+        let delta = (to - from).abs();
+
+        match self.boundary_mode() {
+            BoundaryMode::Wrap => delta.min(self.board_size() - delta).element_sum(),
+            BoundaryMode::Solid => delta.element_sum(),
+        }
+    }
+}
+
+/// Heads for the closest food as the crow flies, as long as the next cell is safe.
+pub struct Greedy;
+
+impl SnakeController for Greedy {
+    fn next_dir(&mut self, view: &BoardView) -> Option<Dir> {
+        let head = view.snake().head().coords;
+        let closest = |coords: Vec2| {
+            view.food()
+                .iter()
+                .map(|food| view.distance(coords, food.coords))
+                .fold(f32::INFINITY, f32::min)
+        };
+
+        // Ties keep the safe direction that came first, so the snake prefers going straight.
+        view.safe_dirs().into_iter().min_by(|&a, &b| {
+            let distance = |dir| closest(view.neighbor(head, dir).expect("Expected safe cell"));
+            distance(a).total_cmp(&distance(b))
+        })
+    }
+}
+
+/// Follows the shortest free path to any food, falling back to any safe move.
+pub struct BfsToFood;
+
+impl SnakeController for BfsToFood {
+    fn next_dir(&mut self, view: &BoardView) -> Option<Dir> {
+        let safe_dirs = view.safe_dirs();
+        let head = view.snake().head().coords;
+        let size = view.board_size();
+        let index = |coords: Vec2| (coords.y * size.x + coords.x) as usize;
+
+        let mut visited = vec![false; size.element_product() as usize];
+        visited[index(head)] = true;
+        let mut queue = VecDeque::new();
+        for &dir in &safe_dirs {
+            let coords = view.neighbor(head, dir).expect("Expected safe cell");
+            visited[index(coords)] = true;
+            queue.push_back((coords, dir));
+        }
+
+        while let Some((coords, first_dir)) = queue.pop_front() {
+            if view.food().iter().any(|food| food.coords == coords) {
+                return Some(first_dir);
+            }
+
+            for dir in DIRS {
+                let Some(next) = view.neighbor(coords, dir) else {
+                    continue;
+                };
+                if !visited[index(next)] && view.is_free(next) {
+                    visited[index(next)] = true;
+                    queue.push_back((next, first_dir));
+                }
+            }
+        }
+
+        safe_dirs.first().copied()
+    }
+}
+
+/// Walks a cycle through every cell of the board, which never collides and
+/// eventually fills the board.
+///
+/// A cycle only exists on open boards with an even side. Anywhere else, or
+/// while the snake is not heading along the cycle yet, it plays like [`BfsToFood`].
+#[derive(Default)]
+pub struct Hamiltonian {
+    cycle: Option<(Vec2, Vec<Dir>)>,
+}
+
+impl Hamiltonian {
+    /// Direction to leave each cell in, indexed by `y * width + x`.
+    fn build_cycle(size: Vec2) -> Option<Vec<Dir>> {
+        let (width, height) = (size.x as usize, size.y as usize);
+        if width < 2 || height < 2 {
+            return None;
+        }
+
+        if height % 2 == 0 {
+            Some(Self::snake_rows(width, height))
+        } else if width % 2 == 0 {
+            // Builds the cycle on the transposed board and swaps the axes back.
+            let transposed = Self::snake_rows(height, width);
+            let mut cycle = vec![Dir::N; width * height];
+            for y in 0..height {
+                for x in 0..width {
+                    cycle[y * width + x] = match transposed[x * height + y] {
+                        Dir::N => Dir::E,
+                        Dir::E => Dir::N,
+                        Dir::S => Dir::W,
+                        Dir::W => Dir::S,
+                    };
+                }
+            }
+            Some(cycle)
+        } else {
+            None
+        }
+    }
+
+    /// Zig-zags east and west through every row but the first column, then
+    /// comes back down the first column. Needs an even height.
+    fn snake_rows(width: usize, height: usize) -> Vec<Dir> {
+        let mut cycle = vec![Dir::N; width * height];
+
+        for y in 0..height {
+            for x in 0..width {
+                cycle[y * width + x] = match (x, y % 2 == 0) {
+                    (0, _) if y > 0 => Dir::S,
+                    (0, _) => Dir::E,
+                    (x, true) if x == width - 1 => Dir::N,
+                    (_, true) => Dir::E,
+                    (1, false) if y == height - 1 => Dir::W,
+                    (1, false) => Dir::N,
+                    (_, false) => Dir::W,
+                };
+            }
+        }
+
+        cycle
+    }
+}
+
+impl SnakeController for Hamiltonian {
+    fn next_dir(&mut self, view: &BoardView) -> Option<Dir> {
+        let size = view.board_size();
+        if self.cycle.as_ref().map(|(cycle_size, _)| *cycle_size) != Some(size) {
+            let cycle = (view.walls().is_empty())
+                .then(|| Self::build_cycle(size))
+                .flatten()
+                .unwrap_or_default();
+            self.cycle = Some((size, cycle));
+        }
+
+        let (_, cycle) = self.cycle.as_ref().expect("Expected cycle");
+        let head = view.snake().head();
+        let dir = cycle
+            .get((head.coords.y * size.x + head.coords.x) as usize)
+            .copied()
+            .filter(|&dir| view.safe_dirs().contains(&dir));
+
+        dir.or_else(|| BfsToFood.next_dir(view))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::SnakeConfig, level::Level, rng::GameRng, sim::SimStatus};
+
+    fn sim(level: &str) -> SnakeSim {
+        let config = SnakeConfig {
+            max_food: 0,
+            boundary_mode: BoundaryMode::Solid,
+            ..default()
+        };
+        SnakeSim::from_level(
+            &config,
+            &Level::parse(level).unwrap(),
+            GameRng::from_seed(0),
+        )
+    }
+
+    #[test]
+    fn greedy_turns_towards_food_and_away_from_walls() {
+        let mut sim = sim(".....\n.>...\n.....\n");
+        sim.add_food(Vec2::new(1.0, 0.0));
+        assert_eq!(Greedy.next_dir(&BoardView::new(&sim, 0)), Some(Dir::S));
+
+        let sim = self::sim(".....\n.>#..\n.....\n");
+        let dir = Greedy.next_dir(&BoardView::new(&sim, 0));
+        assert!(matches!(dir, Some(Dir::N | Dir::S)));
+    }
+
+    #[test]
+    fn bfs_finds_a_way_around_walls() {
+        let mut sim = sim("\
+            ......\n\
+            ..#...\n\
+            .>.#..\n\
+            ..#...\n");
+        sim.add_food(Vec2::new(4.0, 1.0));
+
+        // Straight ahead is closer to the food, but it is a dead end.
+        assert_eq!(Greedy.next_dir(&BoardView::new(&sim, 0)), Some(Dir::E));
+        assert_eq!(BfsToFood.next_dir(&BoardView::new(&sim, 0)), Some(Dir::N));
+    }
+
+    #[test]
+    fn bots_keep_out_of_cells_another_head_can_reach() {
+        let config = SnakeConfig {
+            players: 2,
+            start_length: 2,
+            max_food: 0,
+            boundary_mode: BoundaryMode::Solid,
+            ..default()
+        };
+        let level = Level::parse(".....\n.>...\n..^..\n.....\n").unwrap();
+        let mut sim = SnakeSim::from_level(&config, &level, GameRng::from_seed(0));
+        let contested = Vec2::new(2.0, 2.0);
+        sim.add_food(contested);
+
+        // Both heads are one step from the food, so going for it would be a head-on collision.
+        for player in 0..2 {
+            let view = BoardView::new(&sim, player);
+            let head = view.snake().head().coords;
+            let into_contested =
+                |dir: Option<Dir>| dir.and_then(|dir| view.neighbor(head, dir)) == Some(contested);
+
+            assert!(!view
+                .safe_dirs()
+                .iter()
+                .any(|&dir| into_contested(Some(dir))));
+            assert!(!into_contested(Greedy.next_dir(&view)));
+            assert!(!into_contested(BfsToFood.next_dir(&view)));
+        }
+    }
+
+    #[test]
+    fn hamiltonian_autopilot_fills_the_board() {
+        let config = SnakeConfig {
+            board_size: (6, 4),
+            max_food: 1,
+            ..default()
+        };
+        let mut sim = SnakeSim::new(&config, GameRng::from_seed(5));
+        let mut controller = Hamiltonian::default();
+
+        for _ in 0..2000 {
+            if sim.status() != SimStatus::Running {
+                break;
+            }
+            let dir = controller.next_dir(&BoardView::new(&sim, 0));
+            sim.step(dir);
+        }
+
+        assert_eq!(sim.status(), SimStatus::Won);
+        assert_eq!(sim.snake(0).len(), 24);
+    }
+}
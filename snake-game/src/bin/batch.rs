@@ -0,0 +1,284 @@
+//! Plays many snake games without a window to compare AI controllers.
+//!
+//! ```sh
+//! cargo run --release --bin batch -- --controllers bfs --seeds 0..1000 --format csv
+//! ```
+//!
+//! Board rules are read like the game reads them, from `--config` and the
+//! command line overrides. Every player needs a bot controller.
+
+use std::{
+    fs::File,
+    io::{self, Write},
+    ops::Range,
+    process::ExitCode,
+};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use snake_game::{
+    ai::{BoardView, ControllerKind, SnakeController},
+    config::SnakeConfig,
+    rng::GameRng,
+    sim::{SimStatus, SnakeSim},
+    utils::cli_arg,
+};
+
+const DEFAULT_SEEDS: Range<u64> = 0..100;
+/// Bots that stop eating can circle forever, so games are cut off eventually.
+const DEFAULT_MAX_TICKS: u64 = 100_000;
+
+fn main() -> ExitCode {
+    let batch = match Batch::from_env() {
+        Ok(batch) => batch,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let exit = App::new()
+        .add_plugins(MinimalPlugins)
+        .insert_resource(batch)
+        .add_systems(Startup, start_game)
+        .add_systems(Update, (step_game, finish_game).chain())
+        .run();
+
+    match exit {
+        AppExit::Success => ExitCode::SUCCESS,
+        AppExit::Error(_) => ExitCode::FAILURE,
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(Resource)]
+struct Batch {
+    config: SnakeConfig,
+    seeds: Range<u64>,
+    max_ticks: u64,
+    format: Format,
+    output: Option<String>,
+    controllers: Vec<Box<dyn SnakeController>>,
+    results: Vec<GameStats>,
+}
+
+impl Batch {
+    fn from_env() -> Result<Self, String> {
+        let config = SnakeConfig::from_env().map_err(|err| err.to_string())?;
+
+        if let Some(player) = (0..config.players)
+            .find(|&player| config.controller(player) == ControllerKind::Keyboard)
+        {
+            return Err(format!(
+                "player {} has no bot, pick one with --controllers",
+                player + 1
+            ));
+        }
+
+        let seeds = match cli_arg("seeds") {
+            Some(seeds) => parse_seeds(&seeds).ok_or(format!("invalid --seeds {:?}", seeds))?,
+            None => DEFAULT_SEEDS,
+        };
+        let max_ticks = match cli_arg("max-ticks") {
+            Some(max_ticks) => max_ticks
+                .parse()
+                .map_err(|_| format!("invalid --max-ticks {:?}", max_ticks))?,
+            None => DEFAULT_MAX_TICKS,
+        };
+        let format = match cli_arg("format").as_deref() {
+            None | Some("csv") => Format::Csv,
+            Some("json") => Format::Json,
+            Some(format) => return Err(format!("invalid --format {:?}", format)),
+        };
+
+        Ok(Self {
+            config,
+            seeds,
+            max_ticks,
+            format,
+            output: cli_arg("output"),
+            controllers: vec![],
+            results: vec![],
+        })
+    }
+
+    /// Sets up the game for the next seed, with fresh controllers.
+    fn next_game(&mut self) -> Option<SnakeSim> {
+        let seed = self.seeds.next()?;
+
+        self.controllers = (0..self.config.players)
+            .map(|player| {
+                self.config
+                    .controller(player)
+                    .build()
+                    .expect("Expected a bot for every player")
+            })
+            .collect();
+
+        Some(SnakeSim::new(&self.config, GameRng::from_seed(seed)))
+    }
+}
+
+/// Parses `10..20`, or a single seed.
+fn parse_seeds(seeds: &str) -> Option<Range<u64>> {
+    match seeds.split_once("..") {
+        Some((start, end)) => Some(start.parse().ok()?..end.parse().ok()?),
+        None => seeds.parse().ok().map(|seed| seed..seed + 1),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum GameResult {
+    Won,
+    Lost,
+    /// Still running after the tick limit.
+    Timeout,
+}
+
+/// How a single game went.
+#[derive(Serialize)]
+struct GameStats {
+    seed: u64,
+    result: GameResult,
+    /// Player who won the round, numbered from 1.
+    winner: Option<usize>,
+    ticks: u64,
+    /// Length reached by each player's snake.
+    lengths: Vec<usize>,
+}
+
+fn start_game(
+    mut commands: Commands,
+    mut batch: ResMut<Batch>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    match batch.next_game() {
+        Some(sim) => commands.insert_resource(sim),
+        None => {
+            app_exit.send(AppExit::Success);
+        }
+    }
+}
+
+fn step_game(mut batch: ResMut<Batch>, sim: Option<ResMut<SnakeSim>>) {
+    let Some(mut sim) = sim else {
+        return;
+    };
+
+    for (player, controller) in batch.controllers.iter_mut().enumerate() {
+        if !sim.snake(player).is_alive() {
+            continue;
+        }
+
+        if let Some(dir) = controller.next_dir(&BoardView::new(&sim, player)) {
+            sim.queue_input(player, dir);
+        }
+    }
+
+    sim.step_queued();
+}
+
+fn finish_game(
+    mut commands: Commands,
+    mut batch: ResMut<Batch>,
+    sim: Option<Res<SnakeSim>>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    let Some(sim) = sim else {
+        return;
+    };
+
+    let result = match sim.status() {
+        SimStatus::Running if sim.ticks() < batch.max_ticks => return,
+        SimStatus::Running => GameResult::Timeout,
+        SimStatus::Won => GameResult::Won,
+        SimStatus::Lost => GameResult::Lost,
+    };
+
+    batch.results.push(GameStats {
+        seed: sim.seed(),
+        result,
+        winner: sim.winner().map(|winner| winner + 1),
+        ticks: sim.ticks(),
+        lengths: sim.snakes().iter().map(|snake| snake.len()).collect(),
+    });
+
+    if let Some(sim) = batch.next_game() {
+        commands.insert_resource(sim);
+        return;
+    }
+
+    commands.remove_resource::<SnakeSim>();
+    if let Err(err) = write_results(&batch) {
+        eprintln!("Could not write results: {}", err);
+        app_exit.send(AppExit::error());
+    } else {
+        app_exit.send(AppExit::Success);
+    }
+}
+
+fn write_results(batch: &Batch) -> io::Result<()> {
+    let mut output: Box<dyn Write> = match &batch.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    match batch.format {
+        Format::Csv => {
+            let players = batch.config.players;
+            let lengths = (1..=players)
+                .map(|player| format!("length_p{}", player))
+                .collect::<Vec<_>>();
+            writeln!(output, "seed,result,winner,ticks,{}", lengths.join(","))?;
+
+            for stats in &batch.results {
+                let result = serde_json::to_value(&stats.result)?;
+                let lengths = stats.lengths.iter().map(usize::to_string);
+                writeln!(
+                    output,
+                    "{},{},{},{},{}",
+                    stats.seed,
+                    result.as_str().unwrap_or_default(),
+                    stats
+                        .winner
+                        .map(|winner| winner.to_string())
+                        .unwrap_or_default(),
+                    stats.ticks,
+                    lengths.collect::<Vec<_>>().join(",")
+                )?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut output, &batch.results)?;
+            writeln!(output)?;
+        }
+    }
+
+    let games = batch.results.len().max(1) as f32;
+    let wins = batch
+        .results
+        .iter()
+        .filter(|stats| matches!(stats.result, GameResult::Won))
+        .count();
+    let mean_length = batch
+        .results
+        .iter()
+        .map(|stats| stats.lengths[0] as f32)
+        .sum::<f32>()
+        / games;
+    eprintln!(
+        "{} games, {:.1}% won, mean length {:.1}",
+        batch.results.len(),
+        wins as f32 / games * 100.0,
+        mean_length
+    );
+
+    Ok(())
+}
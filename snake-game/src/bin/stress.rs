@@ -0,0 +1,97 @@
+//! Plays a game with a snake thousands of segments long and logs the frame time, to see how
+//! drawing keeps up with a long snake.
+//!
+//! ```sh
+//! cargo run --release --bin stress -- --length 5000
+//! ```
+//!
+//! The snake starts in a straight line on a wrapping board twice its length, so it can go on
+//! without steering. Other rules are read like the game reads them.
+
+use std::process::ExitCode;
+
+use bevy::{
+    diagnostic::{EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+    window::PresentMode,
+};
+
+use snake_game::{
+    config::SnakeConfig,
+    game::{GamePlugin, GameState},
+    sim::BoundaryMode,
+    snake::GRID_SIZE,
+    utils::cli_arg,
+};
+
+const DEFAULT_LENGTH: usize = 2000;
+/// A few rows are enough to fit the snake, and keep it visible when zoomed out.
+const BOARD_HEIGHT: u32 = 3;
+const WINDOW_WIDTH: f32 = 1000.0;
+
+fn main() -> ExitCode {
+    let config = match stress_config() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let zoom = config.board_size().x * GRID_SIZE / WINDOW_WIDTH;
+
+    let exit = App::new()
+        .add_plugins((
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        resolution: (WINDOW_WIDTH, WINDOW_WIDTH / 2.0).into(),
+                        // Frames are timed as fast as they can be drawn.
+                        present_mode: PresentMode::AutoNoVsync,
+                        ..default()
+                    }),
+                    ..default()
+                }),
+            FrameTimeDiagnosticsPlugin,
+            EntityCountDiagnosticsPlugin,
+            LogDiagnosticsPlugin::default(),
+        ))
+        .add_plugins(GamePlugin)
+        .insert_resource(config)
+        .add_systems(Startup, move |mut commands: Commands| {
+            let mut camera = Camera2dBundle::default();
+            camera.projection.scale = zoom;
+            commands.spawn(camera);
+        })
+        .add_systems(Startup, |mut next_state: ResMut<NextState<GameState>>| {
+            next_state.set(GameState::InGame)
+        })
+        .run();
+
+    match exit {
+        AppExit::Success => ExitCode::SUCCESS,
+        AppExit::Error(_) => ExitCode::FAILURE,
+    }
+}
+
+/// The game's config, with a snake of `--length` segments on a board it fits on.
+fn stress_config() -> Result<SnakeConfig, String> {
+    let length = match cli_arg("length") {
+        Some(length) => length
+            .parse()
+            .map_err(|_| format!("invalid value {:?} for --length", length))?,
+        None => DEFAULT_LENGTH,
+    };
+
+    let config = SnakeConfig {
+        board_size: (2 * length as u32, BOARD_HEIGHT),
+        players: 1,
+        start_length: length,
+        boundary_mode: BoundaryMode::Wrap,
+        level: None,
+        ..SnakeConfig::from_env().map_err(|err| err.to_string())?
+    };
+
+    config.validate().map_err(|err| err.to_string())?;
+    Ok(config)
+}
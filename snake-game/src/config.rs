@@ -0,0 +1,603 @@
+use std::{error::Error, fmt, fs, io, path::Path, str::FromStr, time::Duration};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ai::ControllerKind, food::FoodKind, level::Level, sim::BoundaryMode, theme::THEMES,
+    utils::cli_arg,
+};
+
+pub const CONFIG_PATH: &str = "snake.ron";
+/// Each player needs their own keys, so local games are capped.
+pub const MAX_PLAYERS: usize = 2;
+/// Widest or tallest a board can be, which still fits the stress test's long snake.
+pub const MAX_BOARD_SIZE: u32 = 10_000;
+/// Most cells a board can have, as every cell is tracked while playing.
+pub const MAX_BOARD_CELLS: u64 = 1_000_000;
+/// Longest any timing setting can be. Longer ones gain nothing and overflow game time.
+pub const MAX_SECONDS: f32 = 24.0 * 60.0 * 60.0;
+
+/// Rules for a snake game, read from a RON file and overridable from the command line.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnakeConfig {
+    /// Width and height of the board in cells.
+    pub board_size: (u32, u32),
+    /// Number of snakes sharing the board.
+    pub players: usize,
+    /// Who steers each player's snake. Players without an entry use the keyboard.
+    pub controllers: Vec<ControllerKind>,
+    /// Seconds between game ticks at the start of a game.
+    pub tick: f32,
+    /// How the tick shortens as the snake grows.
+    pub speed_curve: SpeedCurve,
+    /// Shortest tick the speed curve can reach, in seconds.
+    pub min_tick: f32,
+    pub start_length: usize,
+    pub max_food: usize,
+    /// Seconds before another piece of food is spawned.
+    pub food_interval: f32,
+    /// How often each kind of food spawns, relative to the others.
+    pub food_weights: FoodWeights,
+    /// Seconds special food stays on the board before it disappears.
+    pub special_food_lifetime: f32,
+    /// Seconds the speed and ghost effects of special food last.
+    pub effect_duration: f32,
+    pub boundary_mode: BoundaryMode,
+    /// Turns a player can queue up ahead of their snake. Further inputs are dropped.
+    pub input_buffer: usize,
+    /// Slides the snake between cells every frame instead of jumping once per tick.
+    pub interpolate: bool,
+    /// Asset path of the `.theme.ron` file the snake and food are drawn with.
+    pub theme: String,
+    /// Asset path of a `.level` file to play on instead of an open board.
+    pub level: Option<String>,
+}
+
+impl Default for SnakeConfig {
+    fn default() -> Self {
+        Self {
+            board_size: (15, 15),
+            players: 1,
+            controllers: vec![],
+            tick: 0.2,
+            speed_curve: SpeedCurve::Exponential(0.97),
+            min_tick: 0.08,
+            start_length: 2,
+            max_food: 3,
+            food_interval: 1.0,
+            food_weights: default(),
+            special_food_lifetime: 6.0,
+            effect_duration: 5.0,
+            boundary_mode: BoundaryMode::Wrap,
+            input_buffer: 3,
+            interpolate: false,
+            theme: THEMES[0].to_string(),
+            level: None,
+        }
+    }
+}
+
+impl SnakeConfig {
+    /// Reads the file given by `--config` (or [`CONFIG_PATH`] if it exists),
+    /// then applies any command line overrides.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let mut config = match cli_arg("config") {
+            Some(path) => Self::load(path)?,
+            None => match Self::load(CONFIG_PATH) {
+                Err(ConfigError::Io(err)) if err.kind() == io::ErrorKind::NotFound => default(),
+                config => config?,
+            },
+        };
+
+        if let Some(board_size) = cli_arg("board-size") {
+            config.board_size = parse_board_size(&board_size)?;
+        }
+        override_from_cli(&mut config.players, "players")?;
+        if let Some(controllers) = cli_arg("controllers") {
+            config.controllers = controllers
+                .split(',')
+                .map(|controller| parse_controller(controller.trim()))
+                .collect::<Result<_, _>>()?;
+        }
+        override_from_cli(&mut config.tick, "tick")?;
+        override_from_cli(&mut config.min_tick, "min-tick")?;
+        override_from_cli(&mut config.start_length, "start-length")?;
+        override_from_cli(&mut config.max_food, "max-food")?;
+        override_from_cli(&mut config.food_interval, "food-interval")?;
+        if let Some(boundary_mode) = cli_arg("boundary") {
+            config.boundary_mode = match boundary_mode.as_str() {
+                "wrap" => BoundaryMode::Wrap,
+                "solid" => BoundaryMode::Solid,
+                _ => return Err(ConfigError::InvalidArgument("boundary", boundary_mode)),
+            };
+        }
+
+        override_from_cli(&mut config.input_buffer, "input-buffer")?;
+        override_from_cli(&mut config.interpolate, "interpolate")?;
+        if let Some(theme) = cli_arg("theme") {
+            config.theme = theme;
+        }
+        if let Some(level) = cli_arg("level") {
+            config.level = Some(level);
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let config: Self = ron::from_str(&fs::read_to_string(path).map_err(ConfigError::Io)?)
+            .map_err(ConfigError::Parse)?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let (width, height) = self.board_size;
+
+        if width == 0 || height == 0 {
+            return Err(ConfigError::EmptyBoard);
+        }
+        if width > MAX_BOARD_SIZE
+            || height > MAX_BOARD_SIZE
+            || width as u64 * height as u64 > MAX_BOARD_CELLS
+        {
+            return Err(ConfigError::BoardTooLarge(self.board_size));
+        }
+        if !is_positive_seconds(self.tick) {
+            return Err(ConfigError::InvalidTick(self.tick));
+        }
+        if !is_positive_seconds(self.min_tick) {
+            return Err(ConfigError::InvalidTick(self.min_tick));
+        }
+        let is_valid_curve = match self.speed_curve {
+            SpeedCurve::Constant => true,
+            SpeedCurve::Linear(step) => step >= 0.0,
+            SpeedCurve::Exponential(factor) => factor > 0.0 && factor <= 1.0,
+        };
+        if !is_valid_curve {
+            return Err(ConfigError::InvalidSpeedCurve(self.speed_curve));
+        }
+        if !(self.food_interval == 0.0 || is_positive_seconds(self.food_interval)) {
+            return Err(ConfigError::InvalidFoodInterval(self.food_interval));
+        }
+        if self.max_food == 0 {
+            return Err(ConfigError::NoFood);
+        }
+        if self.food_weights.total() == 0 {
+            return Err(ConfigError::NoFoodWeights);
+        }
+        if !is_positive_seconds(self.special_food_lifetime) {
+            return Err(ConfigError::InvalidDuration(
+                "special food lifetime",
+                self.special_food_lifetime,
+            ));
+        }
+        if !is_positive_seconds(self.effect_duration) {
+            return Err(ConfigError::InvalidDuration(
+                "effect duration",
+                self.effect_duration,
+            ));
+        }
+        if self.input_buffer == 0 {
+            return Err(ConfigError::NoInputBuffer);
+        }
+        if !(1..=MAX_PLAYERS).contains(&self.players) {
+            return Err(ConfigError::InvalidPlayers(self.players));
+        }
+        if self.start_length < 2 {
+            return Err(ConfigError::SnakeTooShort(self.start_length));
+        }
+        if Level::open(self.board_size(), self.players)
+            .check_spawn(self.players, self.start_length)
+            .is_err()
+        {
+            return Err(ConfigError::SnakeDoesNotFit {
+                start_length: self.start_length,
+                board_size: self.board_size,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn controller(&self, player: usize) -> ControllerKind {
+        self.controllers.get(player).copied().unwrap_or_default()
+    }
+
+    pub fn board_size(&self) -> Vec2 {
+        Vec2::new(self.board_size.0 as f32, self.board_size.1 as f32)
+    }
+
+    pub fn tick_duration(&self) -> Duration {
+        Duration::from_secs_f32(self.tick)
+    }
+
+    /// Tick duration once the snake has reached the given length.
+    pub fn tick_duration_at(&self, length: usize) -> Duration {
+        let grown = length.saturating_sub(self.start_length) as f32;
+        let tick = match self.speed_curve {
+            SpeedCurve::Constant => self.tick,
+            SpeedCurve::Linear(step) => self.tick - step * grown,
+            SpeedCurve::Exponential(factor) => self.tick * factor.powf(grown),
+        };
+
+        // A floor above the starting tick never slows the game down.
+        Duration::from_secs_f32(tick.max(self.min_tick.min(self.tick)))
+    }
+
+    pub fn food_interval(&self) -> Duration {
+        Duration::from_secs_f32(self.food_interval)
+    }
+
+    pub fn special_food_lifetime(&self) -> Duration {
+        Duration::from_secs_f32(self.special_food_lifetime)
+    }
+
+    pub fn effect_duration(&self) -> Duration {
+        Duration::from_secs_f32(self.effect_duration)
+    }
+}
+
+/// Relative spawn chances of each [`FoodKind`]. A weight of zero never spawns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FoodWeights {
+    pub normal: u32,
+    pub golden: u32,
+    pub shrink: u32,
+    pub speed_up: u32,
+    pub slow_down: u32,
+    pub ghost: u32,
+}
+
+impl Default for FoodWeights {
+    fn default() -> Self {
+        Self {
+            normal: 20,
+            golden: 3,
+            shrink: 2,
+            speed_up: 2,
+            slow_down: 2,
+            ghost: 1,
+        }
+    }
+}
+
+impl FoodWeights {
+    /// Only normal food, like the classic game.
+    pub const CLASSIC: Self = Self {
+        normal: 1,
+        golden: 0,
+        shrink: 0,
+        speed_up: 0,
+        slow_down: 0,
+        ghost: 0,
+    };
+
+    pub fn weight(&self, kind: FoodKind) -> u32 {
+        match kind {
+            FoodKind::Normal => self.normal,
+            FoodKind::Golden => self.golden,
+            FoodKind::Shrink => self.shrink,
+            FoodKind::SpeedUp => self.speed_up,
+            FoodKind::SlowDown => self.slow_down,
+            FoodKind::Ghost => self.ghost,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        FoodKind::ALL.iter().map(|&kind| self.weight(kind)).sum()
+    }
+}
+
+/// How the tick duration changes with every segment the snake grows.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SpeedCurve {
+    Constant,
+    /// Takes this many seconds off the tick.
+    Linear(f32),
+    /// Multiplies the tick by this factor.
+    Exponential(f32),
+}
+
+/// Whether a setting in seconds is above zero and no longer than [`MAX_SECONDS`].
+/// Infinite and NaN values are rejected too.
+fn is_positive_seconds(seconds: f32) -> bool {
+    seconds > 0.0 && seconds <= MAX_SECONDS
+}
+
+fn override_from_cli<T: FromStr>(value: &mut T, name: &'static str) -> Result<(), ConfigError> {
+    if let Some(arg) = cli_arg(name) {
+        *value = arg
+            .parse()
+            .map_err(|_| ConfigError::InvalidArgument(name, arg))?;
+    }
+
+    Ok(())
+}
+
+fn parse_controller(controller: &str) -> Result<ControllerKind, ConfigError> {
+    match controller {
+        "keyboard" => Ok(ControllerKind::Keyboard),
+        "greedy" => Ok(ControllerKind::Greedy),
+        "bfs" => Ok(ControllerKind::Bfs),
+        "hamiltonian" => Ok(ControllerKind::Hamiltonian),
+        _ => Err(ConfigError::InvalidArgument(
+            "controllers",
+            controller.to_string(),
+        )),
+    }
+}
+
+/// Parses `20x15`, or `20` for a square board.
+fn parse_board_size(board_size: &str) -> Result<(u32, u32), ConfigError> {
+    let invalid = || ConfigError::InvalidArgument("board-size", board_size.to_string());
+    let parse = |size: &str| size.trim().parse::<u32>().map_err(|_| invalid());
+
+    match board_size.split_once('x') {
+        Some((width, height)) => Ok((parse(width)?, parse(height)?)),
+        None => parse(board_size).map(|size| (size, size)),
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    InvalidArgument(&'static str, String),
+    EmptyBoard,
+    BoardTooLarge((u32, u32)),
+    InvalidPlayers(usize),
+    InvalidTick(f32),
+    InvalidSpeedCurve(SpeedCurve),
+    InvalidFoodInterval(f32),
+    NoFood,
+    NoFoodWeights,
+    InvalidDuration(&'static str, f32),
+    NoInputBuffer,
+    SnakeTooShort(usize),
+    SnakeDoesNotFit {
+        start_length: usize,
+        board_size: (u32, u32),
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "could not read config: {}", err),
+            ConfigError::Parse(err) => write!(f, "could not parse config: {}", err),
+            ConfigError::InvalidArgument(name, value) => {
+                write!(f, "invalid value {:?} for --{}", value, name)
+            }
+            ConfigError::EmptyBoard => write!(f, "the board needs at least one cell"),
+            ConfigError::BoardTooLarge((width, height)) => write!(
+                f,
+                "the board can be at most {} cells wide or tall and have {} cells, got {}x{}",
+                MAX_BOARD_SIZE, MAX_BOARD_CELLS, width, height
+            ),
+            ConfigError::InvalidPlayers(players) => write!(
+                f,
+                "between 1 and {} players can play, got {}",
+                MAX_PLAYERS, players
+            ),
+            ConfigError::InvalidTick(tick) => write!(
+                f,
+                "tick must be positive and at most {} seconds, got {}",
+                MAX_SECONDS, tick
+            ),
+            ConfigError::InvalidSpeedCurve(curve) => write!(
+                f,
+                "speed curve must not slow the game down, got {:?}",
+                curve
+            ),
+            ConfigError::InvalidFoodInterval(interval) => write!(
+                f,
+                "food interval must be between 0 and {} seconds, got {}",
+                MAX_SECONDS, interval
+            ),
+            ConfigError::NoFood => write!(f, "at least one piece of food must be allowed"),
+            ConfigError::NoFoodWeights => write!(f, "at least one kind of food must spawn"),
+            ConfigError::InvalidDuration(name, duration) => write!(
+                f,
+                "{} must be positive and at most {} seconds, got {}",
+                name, MAX_SECONDS, duration
+            ),
+            ConfigError::NoInputBuffer => write!(f, "the input buffer must hold a turn"),
+            ConfigError::SnakeTooShort(length) => {
+                write!(
+                    f,
+                    "the snake needs a head and a tail, got length {}",
+                    length
+                )
+            }
+            ConfigError::SnakeDoesNotFit {
+                start_length,
+                board_size: (width, height),
+            } => write!(
+                f,
+                "a snake of length {} does not fit on a {}x{} board",
+                start_length, width, height
+            ),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_partial_config_with_defaults() {
+        let config: SnakeConfig =
+            ron::from_str("(board_size: (20, 10), boundary_mode: Solid)").unwrap();
+
+        assert_eq!(config.board_size(), Vec2::new(20.0, 10.0));
+        assert_eq!(config.boundary_mode, BoundaryMode::Solid);
+        assert_eq!(config.max_food, SnakeConfig::default().max_food);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_impossible_settings() {
+        let config = |config: SnakeConfig| config.validate();
+
+        assert!(matches!(
+            config(SnakeConfig {
+                board_size: (4, 4),
+                start_length: 4,
+                ..default()
+            }),
+            Err(ConfigError::SnakeDoesNotFit { .. })
+        ));
+        assert!(matches!(
+            config(SnakeConfig {
+                board_size: (0, 15),
+                ..default()
+            }),
+            Err(ConfigError::EmptyBoard)
+        ));
+        // Too many cells to count in a `u32`.
+        assert!(matches!(
+            config(SnakeConfig {
+                board_size: (70_000, 70_000),
+                ..default()
+            }),
+            Err(ConfigError::BoardTooLarge(_))
+        ));
+        assert!(matches!(
+            config(SnakeConfig {
+                board_size: (MAX_BOARD_SIZE, MAX_BOARD_SIZE),
+                ..default()
+            }),
+            Err(ConfigError::BoardTooLarge(_))
+        ));
+        assert!(config(SnakeConfig {
+            board_size: (MAX_BOARD_SIZE, 3),
+            ..default()
+        })
+        .is_ok());
+        assert!(matches!(
+            config(SnakeConfig {
+                tick: 0.0,
+                ..default()
+            }),
+            Err(ConfigError::InvalidTick(_))
+        ));
+        // Too long to be turned into a `Duration`.
+        assert!(matches!(
+            config(SnakeConfig {
+                tick: f32::INFINITY,
+                ..default()
+            }),
+            Err(ConfigError::InvalidTick(_))
+        ));
+        assert!(matches!(
+            config(SnakeConfig {
+                min_tick: f32::NAN,
+                ..default()
+            }),
+            Err(ConfigError::InvalidTick(_))
+        ));
+        assert!(matches!(
+            config(SnakeConfig {
+                food_interval: 1e30,
+                ..default()
+            }),
+            Err(ConfigError::InvalidFoodInterval(_))
+        ));
+        assert!(matches!(
+            config(SnakeConfig {
+                special_food_lifetime: f32::INFINITY,
+                ..default()
+            }),
+            Err(ConfigError::InvalidDuration("special food lifetime", _))
+        ));
+        assert!(matches!(
+            config(SnakeConfig {
+                effect_duration: -1.0,
+                ..default()
+            }),
+            Err(ConfigError::InvalidDuration("effect duration", _))
+        ));
+        assert!(matches!(
+            config(SnakeConfig {
+                max_food: 0,
+                ..default()
+            }),
+            Err(ConfigError::NoFood)
+        ));
+        assert!(config(SnakeConfig {
+            food_interval: 0.0,
+            ..default()
+        })
+        .is_ok());
+        assert!(matches!(
+            config(SnakeConfig {
+                start_length: 1,
+                ..default()
+            }),
+            Err(ConfigError::SnakeTooShort(1))
+        ));
+        assert!(matches!(
+            config(SnakeConfig {
+                food_weights: FoodWeights {
+                    normal: 0,
+                    ..FoodWeights::CLASSIC
+                },
+                ..default()
+            }),
+            Err(ConfigError::NoFoodWeights)
+        ));
+        assert!(matches!(
+            config(SnakeConfig {
+                input_buffer: 0,
+                ..default()
+            }),
+            Err(ConfigError::NoInputBuffer)
+        ));
+        assert!(config(SnakeConfig {
+            board_size: (4, 4),
+            start_length: 3,
+            ..default()
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn speeds_up_with_length_down_to_floor() {
+        let config = SnakeConfig {
+            tick: 0.2,
+            speed_curve: SpeedCurve::Linear(0.01),
+            min_tick: 0.1,
+            start_length: 2,
+            ..default()
+        };
+
+        assert_eq!(config.tick_duration_at(2), Duration::from_secs_f32(0.2));
+        assert_eq!(config.tick_duration_at(7), Duration::from_secs_f32(0.15));
+        assert_eq!(config.tick_duration_at(100), Duration::from_secs_f32(0.1));
+
+        let config = SnakeConfig {
+            speed_curve: SpeedCurve::Exponential(1.5),
+            ..default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidSpeedCurve(_))
+        ));
+    }
+
+    #[test]
+    fn parses_board_size_argument() {
+        assert_eq!(parse_board_size("20x15").unwrap(), (20, 15));
+        assert_eq!(parse_board_size("12").unwrap(), (12, 12));
+        assert!(parse_board_size("12xx").is_err());
+    }
+}
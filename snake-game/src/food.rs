@@ -1,9 +1,15 @@
-use bevy::{prelude::*, utils::Duration};
-use rand::seq::IteratorRandom;
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    game::{self, GameState},
-    gametick::{self, game_tick_finished}, snake,
+    game::{Restart, RestartSet},
+    gametick::{GameTick, GameTickSet},
+    score::{FOOD_POINTS, GOLDEN_FOOD_POINTS},
+    sim::SnakeSim,
+    theme::{theme_changed, ActiveTheme, Theme},
     utils::coords_to_translation,
 };
 
@@ -11,89 +17,136 @@ pub struct FoodPlugin;
 
 impl Plugin for FoodPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<FoodTimer>()
-            .add_systems(PreStartup, load_assets)
-            .add_systems(
-                PostUpdate,
-                (update_timer, update_food)
-                    .chain()
-                    .run_if(in_state(GameState::InGame))
-                    .run_if(game_tick_finished),
-            )
-            ;
+        app.add_systems(GameTick, update_food.in_set(GameTickSet::Food))
+            .add_systems(Update, retheme_food.run_if(theme_changed))
+            .add_systems(Restart, despawn_food.in_set(RestartSet::Cleanup));
     }
 }
 
-const MAX_FOOD: usize = 3;
-const FOOD_GENERATION_INTERVAL: f32 = 1.0;
+/// Mirrors the food in [`SnakeSim`], which appears when spawned and goes away when eaten or expired.
+fn update_food(
+    mut commands: Commands,
+    foods: Query<(Entity, &Food)>,
+    sim: Res<SnakeSim>,
+    theme: ActiveTheme,
+) {
+    for (food_entity, food) in foods.iter() {
+        if !sim.food().contains(food) {
+            commands.entity(food_entity).despawn();
+        }
+    }
+
+    for &food in sim.food() {
+        if foods.iter().any(|(_, spawned)| *spawned == food) {
+            continue;
+        }
+
+        let mut food_entity = commands.spawn((
+            food,
+            SpatialBundle::from_transform(Transform::from_translation(coords_to_translation(
+                sim.board_size(),
+                Vec2::splat(16.0),
+                food.coords,
+            ))),
+        ));
 
-#[derive(Resource)]
-struct FoodTexture {
-    food: Handle<Image>,
+        // Food spawned before the theme has loaded gets drawn by `retheme_food` once it has.
+        if let Some(theme) = theme.get() {
+            food_entity.insert(food_sprite(theme, food.kind));
+        }
+    }
 }
 
-fn load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let food = asset_server.load("food.png");
+fn retheme_food(mut commands: Commands, foods: Query<(Entity, &Food)>, theme: ActiveTheme) {
+    let Some(theme) = theme.get() else {
+        return;
+    };
 
-    commands.insert_resource(FoodTexture { food });
+    for (food_entity, food) in foods.iter() {
+        commands
+            .entity(food_entity)
+            .insert(food_sprite(theme, food.kind));
+    }
 }
 
-fn update_timer(mut food_timer: ResMut<FoodTimer>, game_ticker: Res<gametick::GameTicker>) {
-    food_timer.0.tick(game_ticker.duration());
+fn food_sprite(theme: &Theme, kind: FoodKind) -> (Sprite, Handle<Image>, TextureAtlas) {
+    theme.sprite(
+        theme.food.get(kind),
+        Sprite {
+            color: kind.color(),
+            ..default()
+        },
+    )
 }
 
-fn update_food(
-    mut commands: Commands,
-    foods: Query<&Food>,
-    mut food_timer: ResMut<FoodTimer>,
-    snake_segments: Query<&snake::components::SnakeSegment>,
-    board_size: Res<game::BoardSize>,
-    food_texture: Res<FoodTexture>,
-) {
-    if foods.iter().len() < MAX_FOOD && food_timer.0.finished() || foods.iter().len() == 0 {
-        let board = (0..(board_size.0.x as usize)).flat_map(|x| {
-            (0..(board_size.0.y as usize)).map(move |y| Vec2::new(x as f32, y as f32))
-        });
-
-        let free_spaces = board.filter(|coords| {
-            snake_segments
-                .iter()
-                .all(|segment| segment.coords != *coords)
-                && foods.iter().all(|food| food.coords != *coords)
-        });
-
-        if let Some(chosen) = free_spaces.choose(&mut rand::thread_rng()) {
-            commands.spawn((
-                Food { coords: chosen },
-                SpriteBundle {
-                    texture: food_texture.food.clone(),
-                    transform: Transform::from_translation(coords_to_translation(
-                        board_size.0,
-                        Vec2::splat(16.0),
-                        chosen,
-                    )),
-                    ..default()
-                },
-            ));
-
-            food_timer.0.reset();
-        }
+fn despawn_food(mut commands: Commands, foods: Query<Entity, With<Food>>) {
+    for food_entity in foods.iter() {
+        commands.entity(food_entity).despawn();
     }
 }
 
-#[derive(Clone, Copy, Component)]
+#[derive(Clone, Copy, Component, Debug, PartialEq)]
 pub struct Food {
     pub coords: Vec2,
+    pub kind: FoodKind,
+    /// Game time after which the food disappears if nobody has eaten it.
+    pub expires_at: Option<Duration>,
 }
 
-#[derive(Resource)]
-pub struct FoodTimer(pub Timer);
+/// What eating a piece of food does to the snake.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FoodKind {
+    /// Grows the snake by one segment.
+    #[default]
+    Normal,
+    /// Grows the snake like normal food, for bonus points.
+    Golden,
+    /// Takes segments off the tail instead of growing.
+    Shrink,
+    /// Shortens the tick for a while.
+    SpeedUp,
+    /// Lengthens the tick for a while.
+    SlowDown,
+    /// Lets the snake pass through itself for a while.
+    Ghost,
+}
+
+impl FoodKind {
+    pub const ALL: [Self; 6] = [
+        Self::Normal,
+        Self::Golden,
+        Self::Shrink,
+        Self::SpeedUp,
+        Self::SlowDown,
+        Self::Ghost,
+    ];
+
+    /// Special food expires, and does something other than just growing the snake.
+    pub fn is_special(self) -> bool {
+        self != FoodKind::Normal
+    }
 
-impl FromWorld for FoodTimer {
-    fn from_world(_world: &mut World) -> Self {
-        FoodTimer(Timer::new(
-            Duration::from_secs_f32(FOOD_GENERATION_INTERVAL),
-            TimerMode::Once,
-        ))
+    /// Whether eating it grows the snake by a segment, once the food reaches the tail.
+    pub fn grows(self) -> bool {
+        matches!(self, FoodKind::Normal | FoodKind::Golden)
+    }
+
+    pub fn points(self) -> u32 {
+        match self {
+            FoodKind::Golden => GOLDEN_FOOD_POINTS,
+            _ => FOOD_POINTS,
+        }
+    }
+
+    /// Tint applied on top of the texture.
+    fn color(self) -> Color {
+        match self {
+            FoodKind::Normal => Color::WHITE,
+            FoodKind::Golden => Color::srgb(1.0, 0.84, 0.0),
+            FoodKind::Shrink => Color::srgb(0.75, 0.5, 1.0),
+            FoodKind::SpeedUp => Color::srgb(1.0, 0.45, 0.35),
+            FoodKind::SlowDown => Color::srgb(0.45, 0.75, 1.0),
+            FoodKind::Ghost => Color::srgba(1.0, 1.0, 1.0, 0.6),
+        }
     }
 }
@@ -1,59 +1,163 @@
 use bevy::{
+    ecs::schedule::ScheduleLabel,
     prelude::*,
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
 };
 
-use crate::{food, gametick, snake};
+use crate::{
+    config::SnakeConfig,
+    food,
+    gametick::{self, GameTick, GameTickSet},
+    level::{self, ActiveLevel},
+    rng::GameRng,
+    score,
+    sim::{BoundaryMode, SimStatus, SnakeSim},
+    snake, theme, ui,
+    utils::coords_to_translation,
+};
 
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameState>()
-            .insert_resource(BoardSize(Vec2::splat(15.0)))
+            .enable_state_scoped_entities::<GameState>()
+            .init_schedule(Restart)
+            .configure_sets(Restart, (RestartSet::Cleanup, RestartSet::Setup).chain())
+            .insert_resource(SnakeConfig::from_env().unwrap_or_else(|err| {
+                error!("Invalid snake config, using the defaults: {}", err);
+                default()
+            }))
+            .insert_resource(GameRng::from_env())
             .add_systems(Startup, draw_border)
             .add_plugins((
                 gametick::GameTickPlugin,
+                level::LevelPlugin,
+                theme::ThemePlugin,
                 snake::SnakePlugin,
                 food::FoodPlugin,
+                score::ScorePlugin,
+                ui::UiPlugin,
             ))
-            .add_systems(Update, check_win);
+            .add_systems(GameTick, check_win.in_set(GameTickSet::Collide))
+            .add_systems(
+                Restart,
+                (reset_rng, despawn_border).in_set(RestartSet::Cleanup),
+            )
+            .add_systems(Restart, draw_border.in_set(RestartSet::Setup));
+
+        for exited in [GameState::MainMenu, GameState::GameWin, GameState::GameOver] {
+            app.add_systems(
+                OnTransition {
+                    exited,
+                    entered: GameState::InGame,
+                },
+                restart,
+            );
+        }
     }
 }
 
+const WALL_COLOR: Color = Color::srgb(0.45, 0.3, 0.2);
+
+#[derive(Component)]
+struct Border;
+
 fn draw_border(
     mut commands: Commands,
-    board_size: Res<BoardSize>,
+    config: Res<SnakeConfig>,
+    active_level: ActiveLevel,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    let material = ColorMaterial::from_color(Color::hsl(0.0, 0.0, 0.0));
-    let mesh = Rectangle::from_size((board_size.0 + 1.0) * snake::GRID_SIZE);
-    commands.spawn(MaterialMesh2dBundle {
-        mesh: Mesh2dHandle(meshes.add(mesh)),
-        material: materials.add(material),
-        transform: Transform::from_xyz(0.0, 0.0, -1000.0),
-        ..default()
-    });
-}
-
-fn check_win(
-    snake: Res<snake::Snake>,
-    board_size: Res<BoardSize>,
-    mut next_state: ResMut<NextState<GameState>>,
-) {
-    if snake.len() as f32 == board_size.0.element_product() {
+    let level = active_level.get();
+
+    // Wrapping boards get a soft margin, solid ones are fenced in by a wall one cell thick.
+    let margin = match config.boundary_mode {
+        BoundaryMode::Wrap => 1.0,
+        BoundaryMode::Solid => 0.0,
+    };
+
+    let mut spawn_rectangle = |size: Vec2, color: Color, z: f32| {
+        commands.spawn((
+            Border,
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(Rectangle::from_size(size * snake::GRID_SIZE))),
+                material: materials.add(ColorMaterial::from_color(color)),
+                transform: Transform::from_xyz(0.0, 0.0, z),
+                ..default()
+            },
+        ));
+    };
+
+    spawn_rectangle(
+        level.board_size + margin,
+        Color::hsl(0.0, 0.0, 0.0),
+        -1000.0,
+    );
+    if config.boundary_mode == BoundaryMode::Solid {
+        spawn_rectangle(level.board_size + 2.0, WALL_COLOR, -1001.0);
+    }
+
+    for &coords in &level.walls {
+        commands.spawn((
+            Border,
+            SpriteBundle {
+                sprite: Sprite {
+                    color: WALL_COLOR,
+                    custom_size: Some(Vec2::splat(snake::GRID_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(coords_to_translation(
+                    level.board_size,
+                    Vec2::splat(snake::GRID_SIZE),
+                    coords,
+                )),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn despawn_border(mut commands: Commands, borders: Query<Entity, With<Border>>) {
+    for border_entity in borders.iter() {
+        commands.entity(border_entity).despawn();
+    }
+}
+
+fn restart(world: &mut World) {
+    world.run_schedule(Restart);
+}
+
+fn reset_rng(mut game_rng: ResMut<GameRng>) {
+    *game_rng = game_rng.restart();
+}
+
+fn check_win(sim: Res<SnakeSim>, mut next_state: ResMut<NextState<GameState>>) {
+    if sim.status() == SimStatus::Won {
         next_state.set(GameState::GameWin);
     }
 }
 
-#[derive(Resource)]
-pub struct BoardSize(pub Vec2);
+/// Runs when a new game is started, tearing the previous one down and setting up a fresh one.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Restart;
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RestartSet {
+    Cleanup,
+    Setup,
+}
 
 #[derive(States, Debug, Hash, Eq, PartialEq, Clone, Default)]
 pub enum GameState {
     #[default]
+    MainMenu,
+    Settings,
+    Controls,
     InGame,
+    Paused,
+    HighScores,
     GameWin,
     GameOver,
 }
@@ -1,38 +1,161 @@
 use std::time::Duration;
 
-use bevy::prelude::*;
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
 
-const GAME_TICK: f32 = 0.2;
+use crate::{
+    config::SnakeConfig,
+    game::{GameState, Restart, RestartSet},
+    sim::{SimStatus, SnakeSim},
+};
 
 pub struct GameTickPlugin;
 
 impl Plugin for GameTickPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(GameTicker(Timer::new(
-            Duration::from_secs_f32(GAME_TICK),
-            TimerMode::Repeating,
-        )))
-        .add_systems(PreUpdate, update_ticker);
+        app.init_resource::<GameTicker>()
+            .init_schedule(GameTick)
+            .configure_sets(
+                GameTick,
+                (
+                    GameTickSet::Input,
+                    GameTickSet::Advance,
+                    GameTickSet::Eat,
+                    GameTickSet::Collide,
+                    GameTickSet::Food,
+                    GameTickSet::Render,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, run_game_ticks.run_if(in_state(GameState::InGame)))
+            .add_systems(Restart, reset_ticker.in_set(RestartSet::Cleanup));
     }
 }
 
-fn update_ticker(mut game_ticker: ResMut<GameTicker>, time: Res<Time>) {
-    game_ticker.0.tick(time.delta());
+/// A long frame runs at most this many ticks, and the game slows down instead of freezing
+/// to catch up.
+const MAX_TICKS_PER_FRAME: u32 = 5;
+
+/// Runs once per game tick, as many times per frame as the time that passed calls for.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameTick;
+
+/// Steps of a [`GameTick`], run in this order.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameTickSet {
+    /// Hands the inputs gathered since the last tick to the sim.
+    Input,
+    Advance,
+    Eat,
+    Collide,
+    Food,
+    /// Mirrors the sim onto the entities drawing it.
+    Render,
+}
+
+fn reset_ticker(mut game_ticker: ResMut<GameTicker>, config: Res<SnakeConfig>) {
+    *game_ticker = GameTicker::new(config.tick_duration());
 }
 
+/// Runs the [`GameTick`] schedule for every tick that is due.
+pub fn run_game_ticks(world: &mut World) {
+    let delta = world.resource::<Time>().delta();
+    world.resource_mut::<GameTicker>().elapsed += delta;
+
+    for _ in 0..MAX_TICKS_PER_FRAME {
+        let mut game_ticker = world.resource_mut::<GameTicker>();
+        let duration = game_ticker.duration;
+        if game_ticker.elapsed < duration {
+            return;
+        }
+        game_ticker.elapsed -= duration;
+
+        world.run_schedule(GameTick);
+
+        // The game over screen has to show the tick that ended the game.
+        if world.resource::<SnakeSim>().status() != SimStatus::Running {
+            world.resource_mut::<GameTicker>().elapsed = Duration::ZERO;
+            return;
+        }
+    }
+
+    let mut game_ticker = world.resource_mut::<GameTicker>();
+    if game_ticker.elapsed >= game_ticker.duration {
+        game_ticker.elapsed = Duration::ZERO;
+    }
+}
+
+/// Game time carried over towards the next tick.
 #[derive(Resource)]
-pub struct GameTicker(Timer);
+pub struct GameTicker {
+    duration: Duration,
+    elapsed: Duration,
+}
+
+impl FromWorld for GameTicker {
+    fn from_world(world: &mut World) -> Self {
+        let tick_duration = world.get_resource::<SnakeConfig>().map_or_else(
+            || SnakeConfig::default().tick_duration(),
+            |config| config.tick_duration(),
+        );
+
+        GameTicker::new(tick_duration)
+    }
+}
 
 impl GameTicker {
-    pub fn finished(&self) -> bool {
-        self.0.finished()
+    fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// How far along the current tick is, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
     }
 
     pub fn duration(&self) -> Duration {
-        self.0.duration()
+        self.duration
+    }
+
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
     }
 }
 
-pub fn game_tick_finished(game_ticker: Res<GameTicker>) -> bool {
-    game_ticker.finished()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::GameRng;
+
+    #[test]
+    fn catches_up_on_missed_ticks_within_limit() {
+        let mut world = World::new();
+        world.insert_resource(SnakeSim::new(&default(), GameRng::from_seed(0)));
+        world.insert_resource(GameTicker::new(Duration::from_millis(100)));
+        world.init_resource::<Time>();
+
+        let mut schedule = Schedule::new(GameTick);
+        schedule.add_systems(|mut sim: ResMut<SnakeSim>| {
+            sim.step_queued();
+        });
+        world.add_schedule(schedule);
+
+        let mut frame = |millis| {
+            world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_millis(millis));
+            run_game_ticks(&mut world);
+            (
+                world.resource::<SnakeSim>().ticks(),
+                world.resource::<GameTicker>().fraction(),
+            )
+        };
+
+        assert_eq!(frame(50), (0, 0.5));
+        assert_eq!(frame(200), (2, 0.5));
+        // A long stall is not made up for all at once.
+        assert_eq!(frame(2000), (2 + MAX_TICKS_PER_FRAME as u64, 0.0));
+    }
 }
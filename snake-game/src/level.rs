@@ -0,0 +1,320 @@
+use std::{error::Error, fmt, io};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+
+use crate::{
+    config::SnakeConfig,
+    game::{GameState, Restart},
+    snake::components::Dir,
+};
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .add_systems(PreStartup, load_level)
+            .add_systems(
+                Update,
+                rebuild_on_level_change.run_if(in_state(GameState::MainMenu)),
+            );
+    }
+}
+
+/// A board layout, read from a `.level` file with one character per cell:
+///
+/// - `.` empty cell
+/// - `#` wall
+/// - `*` cell where food may spawn (food spawns anywhere if there are none)
+/// - `^`, `>`, `v`, `<` a snake's head and the direction it starts moving in
+///
+/// The first line is the top row of the board. Heads are handed out to players
+/// in reading order, and the rest of each snake is laid out straight behind its head.
+#[derive(Asset, TypePath, Clone, Debug, PartialEq)]
+pub struct Level {
+    pub board_size: Vec2,
+    pub walls: Vec<Vec2>,
+    /// Head position and starting direction of each snake.
+    pub spawns: Vec<(Vec2, Dir)>,
+    pub food_zones: Vec<Vec2>,
+}
+
+impl Level {
+    /// An empty board with the snakes stacked down the middle, taking turns facing east and west.
+    pub fn open(board_size: Vec2, players: usize) -> Self {
+        let spawns = (0..players)
+            .map(|player| {
+                let y = board_size.y * (player + 1) as f32 / (players + 1) as f32;
+                let direction = if player % 2 == 0 { Dir::E } else { Dir::W };
+
+                (Vec2::new(board_size.x / 2.0, y).floor(), direction)
+            })
+            .collect();
+
+        Self {
+            board_size,
+            walls: vec![],
+            spawns,
+            food_zones: vec![],
+        }
+    }
+
+    pub fn parse(level: &str) -> Result<Self, LevelError> {
+        let rows = level
+            .lines()
+            .map(str::trim_end)
+            .filter(|row| !row.is_empty())
+            .collect::<Vec<_>>();
+
+        let width = rows.first().ok_or(LevelError::Empty)?.chars().count();
+        let height = rows.len();
+        let mut walls = vec![];
+        let mut spawns = vec![];
+        let mut food_zones = vec![];
+
+        for (row_index, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(LevelError::RaggedRow(row_index + 1));
+            }
+
+            for (x, cell) in row.chars().enumerate() {
+                let coords = Vec2::new(x as f32, (height - 1 - row_index) as f32);
+                let direction = match cell {
+                    '.' => continue,
+                    '#' => {
+                        walls.push(coords);
+                        continue;
+                    }
+                    '*' => {
+                        food_zones.push(coords);
+                        continue;
+                    }
+                    '^' => Dir::N,
+                    '>' => Dir::E,
+                    'v' => Dir::S,
+                    '<' => Dir::W,
+                    _ => return Err(LevelError::UnknownCell(cell, row_index + 1)),
+                };
+
+                spawns.push((coords, direction));
+            }
+        }
+
+        if spawns.is_empty() {
+            return Err(LevelError::MissingSpawn);
+        }
+
+        Ok(Self {
+            board_size: Vec2::new(width as f32, height as f32),
+            walls,
+            spawns,
+            food_zones,
+        })
+    }
+
+    /// Cells the player's snake starts on, head first.
+    pub fn spawn_cells(&self, player: usize, start_length: usize) -> impl Iterator<Item = Vec2> {
+        let (head, direction) = self.spawns[player];
+        let step = Vec2::from(direction);
+        (0..start_length).map(move |index| head - index as f32 * step)
+    }
+
+    /// Checks that every player's snake fits on the board without overlapping walls or each other.
+    pub fn check_spawn(&self, players: usize, start_length: usize) -> Result<(), LevelError> {
+        if self.spawns.len() < players {
+            return Err(LevelError::NotEnoughSpawns(players));
+        }
+
+        let mut occupied = self.walls.clone();
+        for player in 0..players {
+            for coords in self.spawn_cells(player, start_length) {
+                if coords.cmplt(Vec2::ZERO).any()
+                    || coords.cmpge(self.board_size).any()
+                    || occupied.contains(&coords)
+                {
+                    return Err(LevelError::SpawnBlocked(start_length));
+                }
+                occupied.push(coords);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(io::Error),
+    Empty,
+    RaggedRow(usize),
+    UnknownCell(char, usize),
+    MissingSpawn,
+    NotEnoughSpawns(usize),
+    SpawnBlocked(usize),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Io(err) => write!(f, "could not read level: {}", err),
+            LevelError::Empty => write!(f, "the level has no rows"),
+            LevelError::RaggedRow(row) => {
+                write!(f, "row {} is not as wide as the first row", row)
+            }
+            LevelError::UnknownCell(cell, row) => {
+                write!(f, "unknown cell {:?} on row {}", cell, row)
+            }
+            LevelError::MissingSpawn => write!(f, "the level has no snake head"),
+            LevelError::NotEnoughSpawns(players) => {
+                write!(f, "the level has fewer than {} snake heads", players)
+            }
+            LevelError::SpawnBlocked(length) => write!(
+                f,
+                "a snake of length {} does not fit behind every spawn",
+                length
+            ),
+        }
+    }
+}
+
+impl Error for LevelError {}
+
+impl From<io::Error> for LevelError {
+    fn from(err: io::Error) -> Self {
+        LevelError::Io(err)
+    }
+}
+
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Level, LevelError> {
+        let mut level = String::new();
+        reader.read_to_string(&mut level).await?;
+
+        Level::parse(&level)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level"]
+    }
+}
+
+/// The level picked in [`SnakeConfig::level`].
+#[derive(Resource)]
+struct LevelHandle(Handle<Level>);
+
+/// The level the next game is played on.
+#[derive(SystemParam)]
+pub struct ActiveLevel<'w> {
+    config: Res<'w, SnakeConfig>,
+    handle: Option<Res<'w, LevelHandle>>,
+    levels: Res<'w, Assets<Level>>,
+}
+
+impl ActiveLevel<'_> {
+    /// Falls back to an open board while the level is loading or if it cannot fit the snake.
+    pub fn get(&self) -> Level {
+        let level = self
+            .handle
+            .as_ref()
+            .and_then(|handle| self.levels.get(&handle.0));
+
+        let players = self.config.players;
+        let start_length = self.config.start_length;
+
+        match level.map(|level| (level, level.check_spawn(players, start_length))) {
+            Some((level, Ok(()))) => level.clone(),
+            Some((_, Err(err))) => {
+                error!("Cannot play level: {}", err);
+                Level::open(self.config.board_size(), players)
+            }
+            None => Level::open(self.config.board_size(), players),
+        }
+    }
+}
+
+fn load_level(mut commands: Commands, config: Res<SnakeConfig>, asset_server: Res<AssetServer>) {
+    if let Some(path) = &config.level {
+        commands.insert_resource(LevelHandle(asset_server.load(path.clone())));
+    }
+}
+
+/// Redraws the board behind the main menu once the level has loaded or its file changes.
+fn rebuild_on_level_change(
+    mut commands: Commands,
+    mut level_events: EventReader<AssetEvent<Level>>,
+    handle: Option<Res<LevelHandle>>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+
+    if level_events
+        .read()
+        .any(|event| event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0))
+    {
+        commands.add(|world: &mut World| world.run_schedule(Restart));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ascii_level() {
+        let level = Level::parse("#####\n#.>*#\n#...#\n").unwrap();
+
+        assert_eq!(level.board_size, Vec2::new(5.0, 3.0));
+        assert_eq!(level.spawns, [(Vec2::new(2.0, 1.0), Dir::E)]);
+        assert_eq!(level.food_zones, [Vec2::new(3.0, 1.0)]);
+        assert_eq!(level.walls.len(), 9);
+        assert!(level.walls.contains(&Vec2::new(0.0, 2.0)));
+        assert!(level.check_spawn(1, 2).is_ok());
+        assert!(matches!(
+            level.check_spawn(1, 3),
+            Err(LevelError::SpawnBlocked(3))
+        ));
+        assert!(matches!(
+            level.check_spawn(2, 2),
+            Err(LevelError::NotEnoughSpawns(2))
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_levels() {
+        assert!(matches!(Level::parse(""), Err(LevelError::Empty)));
+        assert!(matches!(
+            Level::parse("...\n.>\n"),
+            Err(LevelError::RaggedRow(2))
+        ));
+        assert!(matches!(
+            Level::parse("..x\n.>.\n"),
+            Err(LevelError::UnknownCell('x', 1))
+        ));
+        assert!(matches!(
+            Level::parse("...\n...\n"),
+            Err(LevelError::MissingSpawn)
+        ));
+        assert!(matches!(
+            Level::parse(">.<\n...\n").unwrap().check_spawn(2, 2),
+            Err(LevelError::SpawnBlocked(2))
+        ));
+    }
+}
@@ -0,0 +1,14 @@
+pub mod ai;
+pub mod config;
+pub mod food;
+pub mod game;
+pub mod gametick;
+pub mod level;
+pub mod occupancy;
+pub mod rng;
+pub mod score;
+pub mod sim;
+pub mod snake;
+pub mod theme;
+pub mod ui;
+pub mod utils;
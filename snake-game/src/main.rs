@@ -1,12 +1,6 @@
 use bevy::{prelude::*, window::WindowResolution};
 
-pub mod food;
-mod game;
-mod gametick;
-mod snake;
-pub mod utils;
-
-use game::GamePlugin;
+use snake_game::game::GamePlugin;
 
 fn main() {
     App::new()
@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::config::MAX_PLAYERS;
+
+/// What is on each cell of the board, kept up to date as the game changes so a cell can be
+/// checked without looking through every snake.
+#[derive(Clone, Debug)]
+pub struct OccupancyGrid {
+    size: UVec2,
+    cells: Vec<Cell>,
+    /// Free cells food can spawn on, in no particular order.
+    spawnable: Vec<usize>,
+    /// Where each cell is in `spawnable`, if it is there.
+    spawnable_index: Vec<Option<usize>>,
+    /// Cells with neither a wall nor a snake on them.
+    uncovered: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Cell {
+    wall: bool,
+    food: bool,
+    /// Food can spawn here. Levels without food zones allow it everywhere.
+    food_zone: bool,
+    /// Segments of each player's snake on the cell. A ghost's head can share a cell with its body.
+    segments: [u16; MAX_PLAYERS],
+}
+
+impl Cell {
+    fn is_free(&self) -> bool {
+        !self.food && !self.is_covered()
+    }
+
+    fn is_covered(&self) -> bool {
+        self.wall || self.segments.iter().any(|&count| count > 0)
+    }
+}
+
+impl OccupancyGrid {
+    /// An empty board apart from its walls.
+    pub fn new(board_size: Vec2, walls: &[Vec2], food_zones: &[Vec2]) -> Self {
+        let size = board_size.as_uvec2();
+        let cell_count = size.element_product() as usize;
+        let mut grid = Self {
+            size,
+            cells: vec![default(); cell_count],
+            spawnable: vec![],
+            spawnable_index: vec![None; cell_count],
+            uncovered: cell_count,
+        };
+
+        for &coords in walls {
+            if let Some(index) = grid.index(coords) {
+                if !grid.cells[index].wall {
+                    grid.cells[index].wall = true;
+                    grid.uncovered -= 1;
+                }
+            }
+        }
+        for index in 0..cell_count {
+            let coords = grid.coords(index);
+            grid.cells[index].food_zone = food_zones.is_empty() || food_zones.contains(&coords);
+            grid.update_spawnable(index);
+        }
+
+        grid
+    }
+
+    pub fn contains(&self, coords: Vec2) -> bool {
+        self.index(coords).is_some()
+    }
+
+    /// Whether nothing is on the cell. Cells off the board are never free.
+    pub fn is_free(&self, coords: Vec2) -> bool {
+        self.cell(coords).is_some_and(Cell::is_free)
+    }
+
+    pub fn is_wall(&self, coords: Vec2) -> bool {
+        self.cell(coords).is_some_and(|cell| cell.wall)
+    }
+
+    pub fn has_food(&self, coords: Vec2) -> bool {
+        self.cell(coords).is_some_and(|cell| cell.food)
+    }
+
+    /// Number of the player's snake segments on the cell.
+    pub fn segments(&self, coords: Vec2, player: usize) -> u16 {
+        self.cell(coords).map_or(0, |cell| cell.segments[player])
+    }
+
+    /// Whether any snake but the player's is on the cell.
+    pub fn has_other_snake(&self, coords: Vec2, player: usize) -> bool {
+        self.cell(coords).is_some_and(|cell| {
+            cell.segments
+                .iter()
+                .enumerate()
+                .any(|(other, &count)| other != player && count > 0)
+        })
+    }
+
+    /// Whether walls and snakes cover every cell, so the board has been filled.
+    pub fn is_filled(&self) -> bool {
+        self.uncovered == 0
+    }
+
+    /// A random free cell food can spawn on.
+    pub fn random_spawnable(&self, rng: &mut impl Rng) -> Option<Vec2> {
+        if self.spawnable.is_empty() {
+            return None;
+        }
+
+        let index = self.spawnable[rng.gen_range(0..self.spawnable.len())];
+        Some(self.coords(index))
+    }
+
+    pub fn add_segment(&mut self, coords: Vec2, player: usize) {
+        self.update(coords, |cell| cell.segments[player] += 1);
+    }
+
+    pub fn remove_segment(&mut self, coords: Vec2, player: usize) {
+        self.update(coords, |cell| cell.segments[player] -= 1);
+    }
+
+    pub fn set_food(&mut self, coords: Vec2, has_food: bool) {
+        self.update(coords, |cell| cell.food = has_food);
+    }
+
+    /// Changes a cell, ignoring cells off the board like a head that ran out of a solid board.
+    fn update(&mut self, coords: Vec2, change: impl FnOnce(&mut Cell)) {
+        if let Some(index) = self.index(coords) {
+            let was_covered = self.cells[index].is_covered();
+            change(&mut self.cells[index]);
+            match (was_covered, self.cells[index].is_covered()) {
+                (false, true) => self.uncovered -= 1,
+                (true, false) => self.uncovered += 1,
+                _ => {}
+            }
+            self.update_spawnable(index);
+        }
+    }
+
+    fn update_spawnable(&mut self, index: usize) {
+        let cell = self.cells[index];
+        let is_spawnable = cell.food_zone && cell.is_free();
+
+        match (is_spawnable, self.spawnable_index[index]) {
+            (true, None) => {
+                self.spawnable_index[index] = Some(self.spawnable.len());
+                self.spawnable.push(index);
+            }
+            (false, Some(position)) => {
+                self.spawnable.swap_remove(position);
+                if let Some(&moved) = self.spawnable.get(position) {
+                    self.spawnable_index[moved] = Some(position);
+                }
+                self.spawnable_index[index] = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn index(&self, coords: Vec2) -> Option<usize> {
+        let is_on_board = coords.cmpge(Vec2::ZERO).all() && coords.cmplt(self.size.as_vec2()).all();
+        is_on_board.then(|| coords.y as usize * self.size.x as usize + coords.x as usize)
+    }
+
+    fn coords(&self, index: usize) -> Vec2 {
+        let width = self.size.x as usize;
+        Vec2::new((index % width) as f32, (index / width) as f32)
+    }
+
+    fn cell(&self, coords: Vec2) -> Option<&Cell> {
+        self.index(coords).map(|index| &self.cells[index])
+    }
+
+    /// Whether both grids hold the same things, however they got there.
+    #[cfg(test)]
+    pub(crate) fn same_cells(&self, other: &Self) -> bool {
+        let mut spawnable = self.spawnable.clone();
+        let mut other_spawnable = other.spawnable.clone();
+        spawnable.sort_unstable();
+        other_spawnable.sort_unstable();
+
+        self.cells == other.cells
+            && spawnable == other_spawnable
+            && self.uncovered == other.uncovered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::GameRng;
+
+    #[test]
+    fn only_spawns_on_free_cells_in_food_zones() {
+        let zone = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 0.0),
+        ];
+        let mut grid = OccupancyGrid::new(Vec2::new(3.0, 2.0), &[Vec2::new(0.0, 0.0)], &zone);
+        grid.add_segment(Vec2::new(1.0, 0.0), 1);
+
+        let mut rng = GameRng::from_seed(0);
+        for _ in 0..10 {
+            assert_eq!(grid.random_spawnable(&mut rng), Some(Vec2::new(2.0, 0.0)));
+        }
+
+        grid.set_food(Vec2::new(2.0, 0.0), true);
+        assert_eq!(grid.random_spawnable(&mut rng), None);
+
+        grid.remove_segment(Vec2::new(1.0, 0.0), 1);
+        assert!(grid.is_free(Vec2::new(1.0, 0.0)));
+        assert_eq!(grid.random_spawnable(&mut rng), Some(Vec2::new(1.0, 0.0)));
+        assert!(!grid.is_free(Vec2::new(-1.0, 0.0)));
+    }
+}
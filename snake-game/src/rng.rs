@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use crate::utils::cli_arg;
+
+const SEED_ENV_VAR: &str = "SNAKE_SEED";
+
+/// Seeded random number generator behind all snake randomness, so that a seed
+/// and the inputs of a game are enough to replay it exactly.
+#[derive(Resource, Clone)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+    is_fixed: bool,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            is_fixed: true,
+        }
+    }
+
+    /// Uses the seed given by `--seed` or `SNAKE_SEED`, or a random one.
+    pub fn from_env() -> Self {
+        let seed = cli_arg("seed")
+            .or_else(|| std::env::var(SEED_ENV_VAR).ok())
+            .and_then(|seed| match seed.parse() {
+                Ok(seed) => Some(seed),
+                Err(_) => {
+                    warn!("Ignoring invalid seed {:?}", seed);
+                    None
+                }
+            });
+
+        seed.map(Self::from_seed).unwrap_or_else(Self::random)
+    }
+
+    fn random() -> Self {
+        Self {
+            is_fixed: false,
+            ..Self::from_seed(rand::random())
+        }
+    }
+
+    /// Rng for the next game: the same seed again if it was given explicitly,
+    /// a new random one otherwise.
+    pub fn restart(&self) -> Self {
+        if self.is_fixed {
+            Self::from_seed(self.seed)
+        } else {
+            Self::random()
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
@@ -0,0 +1,162 @@
+use std::{fs, io, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::sim::BoundaryMode;
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        let high_scores = HighScores::load(HIGH_SCORES_PATH).unwrap_or_else(|err| {
+            if err.kind() != io::ErrorKind::NotFound {
+                warn!(
+                    "Could not load high scores from {}: {}",
+                    HIGH_SCORES_PATH, err
+                );
+            }
+            default()
+        });
+
+        app.insert_resource(high_scores);
+    }
+}
+
+pub const FOOD_POINTS: u32 = 1;
+pub const GOLDEN_FOOD_POINTS: u32 = 5;
+pub const HIGH_SCORES_PATH: &str = "high_scores.ron";
+const MAX_HIGH_SCORES: usize = 10;
+
+/// Points a player has earned this game, kept next to their [`Snake`](crate::snake::Snake).
+#[derive(Component, Default)]
+pub struct Score(pub u32);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+    pub length: usize,
+}
+
+/// The best scores for one combination of board size and boundary mode.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct HighScoreTable {
+    board_size: (u32, u32),
+    boundary_mode: BoundaryMode,
+    entries: Vec<HighScore>,
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    tables: Vec<HighScoreTable>,
+}
+
+impl HighScores {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        ron::from_str(&fs::read_to_string(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let high_scores = ron::ser::to_string_pretty(self, default())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        fs::write(path, high_scores)
+    }
+
+    /// Entries for the given mode, best first.
+    pub fn entries(&self, board_size: Vec2, boundary_mode: BoundaryMode) -> &[HighScore] {
+        self.table(board_size, boundary_mode)
+            .map(|table| table.entries.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn qualifies(&self, board_size: Vec2, boundary_mode: BoundaryMode, score: u32) -> bool {
+        let entries = self.entries(board_size, boundary_mode);
+
+        score > 0
+            && (entries.len() < MAX_HIGH_SCORES
+                || entries.last().is_some_and(|lowest| score > lowest.score))
+    }
+
+    pub fn insert(&mut self, board_size: Vec2, boundary_mode: BoundaryMode, entry: HighScore) {
+        let board_size = (board_size.x as u32, board_size.y as u32);
+        let index = match self.tables.iter().position(|table| {
+            table.board_size == board_size && table.boundary_mode == boundary_mode
+        }) {
+            Some(index) => index,
+            None => {
+                self.tables.push(HighScoreTable {
+                    board_size,
+                    boundary_mode,
+                    entries: vec![],
+                });
+                self.tables.len() - 1
+            }
+        };
+
+        let entries = &mut self.tables[index].entries;
+        let position = entries.partition_point(|other| other.score >= entry.score);
+        entries.insert(position, entry);
+        entries.truncate(MAX_HIGH_SCORES);
+    }
+
+    fn table(&self, board_size: Vec2, boundary_mode: BoundaryMode) -> Option<&HighScoreTable> {
+        let board_size = (board_size.x as u32, board_size.y as u32);
+        self.tables
+            .iter()
+            .find(|table| table.board_size == board_size && table.boundary_mode == boundary_mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: u32) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            length: score as usize + 2,
+        }
+    }
+
+    #[test]
+    fn keeps_best_scores_first_and_capped() {
+        let board_size = Vec2::splat(15.0);
+        let mut high_scores = HighScores::default();
+
+        for score in 1..=12 {
+            high_scores.insert(board_size, BoundaryMode::Wrap, entry("a", score));
+        }
+        high_scores.insert(board_size, BoundaryMode::Wrap, entry("b", 7));
+
+        let scores = high_scores
+            .entries(board_size, BoundaryMode::Wrap)
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.score))
+            .collect::<Vec<_>>();
+        assert_eq!(scores.len(), MAX_HIGH_SCORES);
+        assert_eq!(scores[0], ("a", 12));
+        assert_eq!(&scores[5..7], [("a", 7), ("b", 7)]);
+
+        assert!(!high_scores.qualifies(board_size, BoundaryMode::Wrap, 4));
+        assert!(high_scores.qualifies(board_size, BoundaryMode::Wrap, 5));
+    }
+
+    #[test]
+    fn separates_tables_by_mode() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(Vec2::splat(15.0), BoundaryMode::Wrap, entry("a", 3));
+
+        assert!(high_scores
+            .entries(Vec2::splat(15.0), BoundaryMode::Solid)
+            .is_empty());
+        assert!(high_scores
+            .entries(Vec2::splat(10.0), BoundaryMode::Wrap)
+            .is_empty());
+        assert!(high_scores.qualifies(Vec2::splat(15.0), BoundaryMode::Solid, 1));
+        assert!(!high_scores.qualifies(Vec2::splat(15.0), BoundaryMode::Solid, 0));
+    }
+}
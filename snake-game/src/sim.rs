@@ -0,0 +1,1045 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::SnakeConfig,
+    food::{Food, FoodKind},
+    level::Level,
+    occupancy::OccupancyGrid,
+    rng::GameRng,
+    snake::components::{Dir, SnakeSegment},
+};
+
+/// Segments taken off the tail by [`FoodKind::Shrink`].
+const SHRINK_SEGMENTS: usize = 2;
+/// Multiplies the tick while [`FoodKind::SpeedUp`] is in effect.
+const SPEED_UP_FACTOR: f32 = 0.6;
+/// Multiplies the tick while [`FoodKind::SlowDown`] is in effect.
+const SLOW_DOWN_FACTOR: f32 = 1.5;
+
+/// What happens when the snake moves past the edge of the board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BoundaryMode {
+    /// Comes back in on the opposite side.
+    #[default]
+    Wrap,
+    /// Hits a wall and loses.
+    Solid,
+}
+
+/// Whether the simulated game is still being played.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SimStatus {
+    #[default]
+    Running,
+    /// The board was filled, nothing was left to eat, or every other snake died.
+    Won,
+    /// Every snake died.
+    Lost,
+}
+
+/// What happened during a single [`SnakeSim::step`].
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct StepOutcome {
+    pub status: SimStatus,
+    /// What happened to each snake, in player order.
+    pub snakes: Vec<SnakeOutcome>,
+    pub spawned: Option<Food>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SnakeOutcome {
+    /// The head moved on to the next cell. Dead snakes stay where they are.
+    pub moved: bool,
+    /// A new body segment was added behind the tail.
+    pub grew: bool,
+    pub eaten: Option<Food>,
+    pub died: bool,
+}
+
+/// One player's snake inside a [`SnakeSim`].
+///
+/// Segments are stored head first and tail last, using the same
+/// [`SnakeSegment`] values that end up on the rendered entities. Moving adds a segment at the
+/// head and drops the tail, so the segments in between are never touched.
+#[derive(Clone, Debug)]
+pub struct SimSnake {
+    segments: VecDeque<SnakeSegment>,
+    input_queue: VecDeque<Dir>,
+    is_alive: bool,
+    /// Game time left before [`FoodKind::Ghost`] wears off.
+    ghost_time: Duration,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl SimSnake {
+    fn new(segments: VecDeque<SnakeSegment>) -> Self {
+        Self {
+            segments,
+            input_queue: VecDeque::new(),
+            is_alive: true,
+            ghost_time: Duration::ZERO,
+        }
+    }
+
+    pub fn segments(&self) -> &VecDeque<SnakeSegment> {
+        &self.segments
+    }
+
+    pub fn head(&self) -> &SnakeSegment {
+        &self.segments[0]
+    }
+
+    pub fn tail(&self) -> &SnakeSegment {
+        &self.segments[self.segments.len() - 1]
+    }
+
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Dead snakes stop moving and stay on the board until the round ends.
+    pub fn is_alive(&self) -> bool {
+        self.is_alive
+    }
+
+    /// Ghosts can move through their own body, but not through anything else.
+    pub fn is_ghost(&self) -> bool {
+        !self.ghost_time.is_zero()
+    }
+}
+
+/// The snake rules without any rendering, driven one game tick at a time.
+///
+/// Every player has their own snake, indexed in player order.
+#[derive(Resource)]
+pub struct SnakeSim {
+    config: SnakeConfig,
+    board_size: Vec2,
+    snakes: Vec<SimSnake>,
+    walls: Vec<Vec2>,
+    food: Vec<Food>,
+    grid: OccupancyGrid,
+    food_timer: Timer,
+    tick_duration: Duration,
+    /// [`FoodKind::SpeedUp`] or [`FoodKind::SlowDown`], and the game time left before it wears off.
+    speed_effect: Option<(FoodKind, Duration)>,
+    rng: GameRng,
+    ticks: u64,
+    elapsed: Duration,
+    status: SimStatus,
+    winner: Option<usize>,
+    is_perfect: bool,
+}
+
+impl SnakeSim {
+    /// Starts a game on an open board with the given rules, which are expected to be valid.
+    pub fn new(config: &SnakeConfig, rng: GameRng) -> Self {
+        Self::from_level(
+            config,
+            &Level::open(config.board_size(), config.players),
+            rng,
+        )
+    }
+
+    /// Starts a game on the given level, which is expected to fit every snake.
+    pub fn from_level(config: &SnakeConfig, level: &Level, rng: GameRng) -> Self {
+        let tail_index = config.start_length - 1;
+
+        let mut grid = OccupancyGrid::new(level.board_size, &level.walls, &level.food_zones);
+        let snakes = (0..config.players)
+            .map(|player| {
+                let direction = level.spawns[player].1;
+                let segments = level
+                    .spawn_cells(player, config.start_length)
+                    .enumerate()
+                    .map(|(index, coords)| match index {
+                        0 => SnakeSegment::make_head(direction, coords).0,
+                        index if index == tail_index => {
+                            SnakeSegment::make_tail(direction, coords).0
+                        }
+                        _ => SnakeSegment::make_body(direction, direction, coords).0,
+                    })
+                    .collect::<VecDeque<_>>();
+
+                for segment in &segments {
+                    grid.add_segment(segment.coords, player);
+                }
+                SimSnake::new(segments)
+            })
+            .collect();
+
+        Self {
+            config: config.clone(),
+            board_size: level.board_size,
+            snakes,
+            walls: level.walls.clone(),
+            food: vec![],
+            grid,
+            food_timer: Timer::new(config.food_interval(), TimerMode::Once),
+            tick_duration: config.tick_duration(),
+            speed_effect: None,
+            rng,
+            ticks: 0,
+            elapsed: Duration::ZERO,
+            status: SimStatus::Running,
+            winner: None,
+            is_perfect: false,
+        }
+    }
+
+    /// The rules this game was started with.
+    pub fn config(&self) -> &SnakeConfig {
+        &self.config
+    }
+
+    pub fn board_size(&self) -> Vec2 {
+        self.board_size
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    /// Number of steps taken so far.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Game time between this tick and the next, which shortens as the snakes grow.
+    pub fn tick_duration(&self) -> Duration {
+        self.tick_duration
+    }
+
+    /// Game time played so far.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn status(&self) -> SimStatus {
+        self.status
+    }
+
+    /// The player who won the round, if the game is won.
+    pub fn winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    /// Whether the game was won by filling every cell of the board.
+    pub fn is_perfect(&self) -> bool {
+        self.is_perfect
+    }
+
+    pub fn snakes(&self) -> &[SimSnake] {
+        &self.snakes
+    }
+
+    pub fn snake(&self, player: usize) -> &SimSnake {
+        &self.snakes[player]
+    }
+
+    pub fn food(&self) -> &[Food] {
+        &self.food
+    }
+
+    pub fn walls(&self) -> &[Vec2] {
+        &self.walls
+    }
+
+    /// What is on each cell of the board.
+    pub fn occupancy(&self) -> &OccupancyGrid {
+        &self.grid
+    }
+
+    /// Places normal food on a free cell, returning whether it was placed.
+    pub fn add_food(&mut self, coords: Vec2) -> bool {
+        self.add_food_of(FoodKind::Normal, coords)
+    }
+
+    /// Places food of the given kind on a free cell, returning whether it was placed.
+    pub fn add_food_of(&mut self, kind: FoodKind, coords: Vec2) -> bool {
+        if !self.grid.is_free(coords) {
+            return false;
+        }
+
+        self.food.push(self.make_food(kind, coords));
+        self.grid.set_food(coords, true);
+        true
+    }
+
+    /// The speed effect currently changing the tick, if any.
+    pub fn speed_effect(&self) -> Option<FoodKind> {
+        self.speed_effect.map(|(kind, _)| kind)
+    }
+
+    /// Whether the player's head is facing food it will reach next tick.
+    pub fn is_anticipating(&self, player: usize) -> bool {
+        let head = self.snakes[player].head();
+        self.grid
+            .has_food(head.coords + Into::<Vec2>::into(head.direction.1))
+    }
+
+    /// Queues a direction for the player's snake to turn in on a coming tick.
+    ///
+    /// Each queued turn is taken on its own tick, so two quick turns make a U-turn.
+    /// Inputs that repeat or reverse the turn before them, or that do not fit in the
+    /// buffer, are dropped.
+    pub fn queue_input(&mut self, player: usize, dir: Dir) {
+        let snake = &mut self.snakes[player];
+        let last = snake
+            .input_queue
+            .back()
+            .copied()
+            .unwrap_or(snake.head().direction.1);
+
+        if !dir.is_parallel(last) && snake.input_queue.len() < self.config.input_buffer {
+            snake.input_queue.push_back(dir);
+        }
+    }
+
+    /// Queues the given inputs for the first player and advances the game by one tick.
+    pub fn step(&mut self, inputs: impl IntoIterator<Item = Dir>) -> StepOutcome {
+        for dir in inputs {
+            self.queue_input(0, dir);
+        }
+        self.step_queued()
+    }
+
+    /// Turns queued for the player's snake, next one first.
+    pub fn queued_inputs(&self, player: usize) -> impl Iterator<Item = Dir> + '_ {
+        self.snakes[player].input_queue.iter().copied()
+    }
+
+    /// Advances the game by one tick, using the inputs queued for every player.
+    pub fn step_queued(&mut self) -> StepOutcome {
+        let mut outcome = StepOutcome {
+            status: self.status,
+            snakes: vec![SnakeOutcome::default(); self.snakes.len()],
+            spawned: None,
+        };
+
+        if self.status != SimStatus::Running {
+            return outcome;
+        }
+
+        self.ticks += 1;
+        self.elapsed += self.tick_duration;
+        self.wear_off_effects();
+
+        for (player, snake_outcome) in outcome.snakes.iter_mut().enumerate() {
+            if self.snakes[player].is_alive {
+                snake_outcome.moved = true;
+                snake_outcome.grew = self.advance(player);
+                snake_outcome.eaten = self.eat(player);
+            }
+        }
+
+        // Every snake moves before anyone collides, so two heads meeting kill both.
+        for (player, snake_outcome) in outcome.snakes.iter_mut().enumerate() {
+            snake_outcome.died = self.snakes[player].is_alive && self.has_collided(player);
+        }
+        for (snake, snake_outcome) in self.snakes.iter_mut().zip(&outcome.snakes) {
+            snake.is_alive &= !snake_outcome.died;
+        }
+
+        let alive = (0..self.snakes.len())
+            .filter(|&player| self.snakes[player].is_alive)
+            .collect::<Vec<_>>();
+
+        if alive.is_empty() {
+            self.status = SimStatus::Lost;
+        } else if self.snakes.len() > 1 && alive.len() == 1 {
+            self.status = SimStatus::Won;
+            self.winner = Some(alive[0]);
+        } else if self.grid.is_filled() {
+            // Growing into the last free cell leaves nowhere to move or spawn food.
+            self.status = SimStatus::Won;
+            self.is_perfect = true;
+            self.winner = alive
+                .into_iter()
+                .max_by_key(|&player| self.snakes[player].len());
+        } else {
+            outcome.spawned = self.spawn_food();
+        }
+
+        let longest = self.snakes.iter().map(SimSnake::len).max().unwrap_or(0);
+        self.tick_duration = match self.speed_effect {
+            Some((FoodKind::SpeedUp, _)) => self
+                .config
+                .tick_duration_at(longest)
+                .mul_f32(SPEED_UP_FACTOR),
+            Some((FoodKind::SlowDown, _)) => self
+                .config
+                .tick_duration_at(longest)
+                .mul_f32(SLOW_DOWN_FACTOR),
+            _ => self.config.tick_duration_at(longest),
+        };
+
+        outcome.status = self.status;
+        outcome
+    }
+
+    fn advance(&mut self, player: usize) -> bool {
+        let (boundary_mode, board_size) = (self.config.boundary_mode, self.board_size);
+        let snake = &mut self.snakes[player];
+        let head_segment = snake.segments[0];
+
+        // Only turns are queued, see `queue_input`.
+        let direction = snake
+            .input_queue
+            .pop_front()
+            .unwrap_or(head_segment.direction.1);
+
+        // The old head stays behind as the neck, turning towards the new head.
+        snake.segments[0].direction = (head_segment.direction.1, direction);
+
+        let coords = head_segment.coords + Into::<Vec2>::into(direction);
+        snake.segments.push_front(SnakeSegment {
+            coords: match boundary_mode {
+                BoundaryMode::Wrap => coords.rem_euclid(board_size),
+                BoundaryMode::Solid => coords,
+            },
+            direction: (direction, direction),
+            is_bloated: false,
+        });
+        self.grid.add_segment(snake.segments[0].coords, player);
+
+        let tail_index = snake.segments.len() - 1;
+        if snake.segments[tail_index].is_bloated {
+            snake.segments[tail_index].is_bloated = false;
+            true
+        } else {
+            // The segment in front of the tail becomes the new tail.
+            let tail_segment = snake.segments.pop_back().expect("Expected tail segment");
+            self.grid.remove_segment(tail_segment.coords, player);
+            false
+        }
+    }
+
+    /// Counts down the effects of special food and removes special food nobody ate in time.
+    fn wear_off_effects(&mut self) {
+        let (elapsed, tick_duration) = (self.elapsed, self.tick_duration);
+
+        for snake in &mut self.snakes {
+            snake.ghost_time = snake.ghost_time.saturating_sub(tick_duration);
+        }
+
+        if let Some((_, time_left)) = &mut self.speed_effect {
+            *time_left = time_left.saturating_sub(tick_duration);
+            if time_left.is_zero() {
+                self.speed_effect = None;
+            }
+        }
+
+        let grid = &mut self.grid;
+        self.food.retain(|food| {
+            let is_fresh = food
+                .expires_at
+                .is_none_or(|expires_at| elapsed < expires_at);
+            if !is_fresh {
+                grid.set_food(food.coords, false);
+            }
+            is_fresh
+        });
+    }
+
+    fn eat(&mut self, player: usize) -> Option<Food> {
+        let head_coords = self.snakes[player].head().coords;
+        if !self.grid.has_food(head_coords) {
+            return None;
+        }
+
+        let index = self
+            .food
+            .iter()
+            .position(|food| food.coords == head_coords)?;
+        let food = self.food.remove(index);
+        self.grid.set_food(food.coords, false);
+        let effect_duration = self.config.effect_duration();
+        let snake = &mut self.snakes[player];
+
+        if food.kind.grows() {
+            snake.segments[0].is_bloated = true;
+        }
+
+        match food.kind {
+            FoodKind::Normal | FoodKind::Golden => {}
+            FoodKind::Shrink => {
+                for _ in 0..SHRINK_SEGMENTS {
+                    // The segment in front of the tail goes, and the tail moves up into its place.
+                    if snake.segments.len() <= 2 {
+                        break;
+                    }
+                    let tail_segment = snake.segments.pop_back().expect("Expected tail segment");
+                    self.grid.remove_segment(tail_segment.coords, player);
+                    if let Some(new_tail) = snake.segments.back_mut() {
+                        new_tail.is_bloated = tail_segment.is_bloated;
+                    }
+                }
+            }
+            FoodKind::SpeedUp | FoodKind::SlowDown => {
+                self.speed_effect = Some((food.kind, effect_duration));
+            }
+            FoodKind::Ghost => snake.ghost_time = effect_duration,
+        }
+
+        self.food_timer.reset();
+        Some(food)
+    }
+
+    fn has_collided(&self, player: usize) -> bool {
+        let head_coords = self.snakes[player].head().coords;
+
+        // The head itself is one of the player's segments on its cell.
+        !self.grid.contains(head_coords)
+            || self.grid.is_wall(head_coords)
+            || (!self.snakes[player].is_ghost() && self.grid.segments(head_coords, player) > 1)
+            || self.grid.has_other_snake(head_coords, player)
+    }
+
+    fn spawn_food(&mut self) -> Option<Food> {
+        self.food_timer.tick(self.tick_duration);
+
+        if self.food.len() >= self.config.max_food
+            || !(self.food_timer.finished() || self.food.is_empty())
+        {
+            return None;
+        }
+
+        let chosen = self.grid.random_spawnable(&mut self.rng)?;
+        let kind = self.random_food_kind();
+        let food = self.make_food(kind, chosen);
+        self.food.push(food);
+        self.grid.set_food(chosen, true);
+        self.food_timer.reset();
+        Some(food)
+    }
+
+    /// Picks a kind of food according to the configured weights.
+    fn random_food_kind(&mut self) -> FoodKind {
+        let weights = self.config.food_weights;
+        let mut roll = self.rng.gen_range(0..weights.total());
+
+        for kind in FoodKind::ALL {
+            if roll < weights.weight(kind) {
+                return kind;
+            }
+            roll -= weights.weight(kind);
+        }
+
+        FoodKind::Normal
+    }
+
+    fn make_food(&self, kind: FoodKind, coords: Vec2) -> Food {
+        Food {
+            coords,
+            kind,
+            expires_at: kind
+                .is_special()
+                .then(|| self.elapsed + self.config.special_food_lifetime()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ai::{BoardView, ControllerKind},
+        config::{FoodWeights, SpeedCurve},
+    };
+
+    fn config(board_size: u32) -> SnakeConfig {
+        SnakeConfig {
+            board_size: (board_size, board_size),
+            max_food: 0,
+            ..default()
+        }
+    }
+
+    fn sim(board_size: u32) -> SnakeSim {
+        SnakeSim::new(&config(board_size), GameRng::from_seed(0))
+    }
+
+    fn replay(sim: &mut SnakeSim, moves: &[Option<Dir>]) -> Vec<StepOutcome> {
+        moves.iter().map(|input| sim.step(*input)).collect()
+    }
+
+    #[test]
+    fn keeps_moving_without_input() {
+        let mut sim = sim(15);
+
+        replay(&mut sim, &[None, None]);
+
+        assert_eq!(sim.snake(0).head().coords, Vec2::new(9.0, 7.0));
+        assert_eq!(sim.snake(0).segments()[1].coords, Vec2::new(8.0, 7.0));
+        assert_eq!(sim.ticks(), 2);
+    }
+
+    #[test]
+    fn ignores_reversing_input() {
+        let mut sim = sim(15);
+
+        sim.step([Dir::W, Dir::N]);
+
+        assert_eq!(sim.snake(0).head().coords, Vec2::new(7.0, 8.0));
+        assert_eq!(sim.snake(0).head().direction, (Dir::N, Dir::N));
+    }
+
+    #[test]
+    fn keeps_leftover_input_for_next_tick() {
+        let mut sim = sim(15);
+
+        sim.step([Dir::N, Dir::W]);
+        sim.step([]);
+
+        assert_eq!(sim.snake(0).head().coords, Vec2::new(6.0, 8.0));
+    }
+
+    #[test]
+    fn takes_fast_double_turn_over_two_ticks() {
+        let mut sim = sim(15);
+
+        // North then west within one tick is a U-turn, not a reversal.
+        sim.step([Dir::N, Dir::W]);
+        assert_eq!(sim.snake(0).head().coords, Vec2::new(7.0, 8.0));
+        assert_eq!(sim.queued_inputs(0).collect::<Vec<_>>(), [Dir::W]);
+
+        sim.step([]);
+        assert_eq!(sim.snake(0).head().coords, Vec2::new(6.0, 8.0));
+        assert_eq!(sim.snake(0).head().direction, (Dir::W, Dir::W));
+        assert!(sim.snake(0).is_alive());
+    }
+
+    #[test]
+    fn drops_repeated_and_reversing_inputs() {
+        let mut sim = sim(15);
+
+        for dir in [Dir::E, Dir::N, Dir::N, Dir::S, Dir::W, Dir::E] {
+            sim.queue_input(0, dir);
+        }
+
+        assert_eq!(sim.queued_inputs(0).collect::<Vec<_>>(), [Dir::N, Dir::W]);
+    }
+
+    #[test]
+    fn limits_queued_inputs_to_buffer() {
+        let config = SnakeConfig {
+            input_buffer: 2,
+            ..config(15)
+        };
+        let mut sim = SnakeSim::new(&config, GameRng::from_seed(0));
+
+        for dir in [Dir::N, Dir::W, Dir::S, Dir::E] {
+            sim.queue_input(0, dir);
+        }
+        assert_eq!(sim.queued_inputs(0).collect::<Vec<_>>(), [Dir::N, Dir::W]);
+
+        // Taking a turn frees up room, and new turns follow on from the last queued one.
+        sim.step([]);
+        sim.queue_input(0, Dir::S);
+        assert_eq!(sim.queued_inputs(0).collect::<Vec<_>>(), [Dir::W, Dir::S]);
+    }
+
+    #[test]
+    fn wraps_around_board_edges() {
+        let mut sim = sim(5);
+
+        let outcomes = replay(&mut sim, &[None, None, None]);
+
+        assert_eq!(sim.snake(0).head().coords, Vec2::new(0.0, 2.0));
+        assert_eq!(sim.snake(0).segments()[1].coords, Vec2::new(4.0, 2.0));
+        assert!(outcomes
+            .iter()
+            .all(|outcome| outcome.status == SimStatus::Running));
+
+        replay(&mut sim, &[Some(Dir::S), None, None]);
+
+        assert_eq!(sim.snake(0).head().coords, Vec2::new(0.0, 4.0));
+    }
+
+    #[test]
+    fn loses_on_solid_boundary() {
+        let config = SnakeConfig {
+            boundary_mode: BoundaryMode::Solid,
+            ..config(5)
+        };
+        let mut sim = SnakeSim::new(&config, GameRng::from_seed(0));
+
+        let outcomes = replay(&mut sim, &[None, None, None]);
+
+        assert_eq!(outcomes[1].status, SimStatus::Running);
+        assert_eq!(outcomes[2].status, SimStatus::Lost);
+    }
+
+    #[test]
+    fn grows_once_eaten_food_reaches_tail() {
+        let mut sim = sim(15);
+        assert!(sim.add_food(Vec2::new(8.0, 7.0)));
+        assert!(sim.is_anticipating(0));
+
+        let outcomes = replay(&mut sim, &[None, None, None]);
+
+        assert_eq!(
+            outcomes[0].snakes[0].eaten.map(|food| food.coords),
+            Some(Vec2::new(8.0, 7.0))
+        );
+        assert!(sim.food().is_empty());
+        assert_eq!(
+            outcomes
+                .iter()
+                .map(|outcome| outcome.snakes[0].grew)
+                .collect::<Vec<_>>(),
+            [false, false, true]
+        );
+        assert_eq!(sim.snake(0).len(), 3);
+        assert_eq!(
+            sim.snake(0)
+                .segments()
+                .iter()
+                .map(|segment| segment.coords)
+                .collect::<Vec<_>>(),
+            [
+                Vec2::new(10.0, 7.0),
+                Vec2::new(9.0, 7.0),
+                Vec2::new(8.0, 7.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn shortens_tick_as_snake_grows() {
+        let config = SnakeConfig {
+            speed_curve: SpeedCurve::Exponential(0.5),
+            min_tick: 0.08,
+            ..config(15)
+        };
+        let mut sim = SnakeSim::new(&config, GameRng::from_seed(0));
+        sim.add_food(Vec2::new(8.0, 7.0));
+
+        replay(&mut sim, &[None, None]);
+        assert_eq!(sim.tick_duration(), config.tick_duration());
+
+        replay(&mut sim, &[None, None]);
+        assert_eq!(sim.tick_duration(), Duration::from_secs_f32(0.1));
+        assert_eq!(
+            sim.elapsed(),
+            config.tick_duration() * 3 + sim.tick_duration()
+        );
+    }
+
+    #[test]
+    fn loses_on_self_collision() {
+        let mut sim = sim(15);
+        for x in 8..11 {
+            sim.add_food(Vec2::new(x as f32, 7.0));
+        }
+
+        replay(&mut sim, &[None; 7]);
+        assert_eq!(sim.snake(0).len(), 5);
+
+        let outcomes = replay(&mut sim, &[Some(Dir::N), Some(Dir::W), Some(Dir::S)]);
+
+        assert_eq!(outcomes[1].status, SimStatus::Running);
+        assert_eq!(outcomes[2].status, SimStatus::Lost);
+        assert_eq!(sim.status(), SimStatus::Lost);
+
+        let head = *sim.snake(0).head();
+        let outcome = sim.step([Dir::E]);
+        assert_eq!(outcome.status, SimStatus::Lost);
+        assert_eq!(*sim.snake(0).head(), head);
+    }
+
+    #[test]
+    fn chasing_the_tail_is_not_a_collision() {
+        let mut sim = sim(15);
+        sim.add_food(Vec2::new(8.0, 7.0));
+        sim.add_food(Vec2::new(9.0, 7.0));
+
+        replay(&mut sim, &[None; 5]);
+        assert_eq!(sim.snake(0).len(), 4);
+
+        let outcomes = replay(
+            &mut sim,
+            &[Some(Dir::N), Some(Dir::W), Some(Dir::S), Some(Dir::E)],
+        );
+
+        assert!(outcomes
+            .iter()
+            .all(|outcome| outcome.status == SimStatus::Running));
+    }
+
+    #[test]
+    fn respects_level_walls_and_food_zones() {
+        let level = Level::parse(
+            "\
+            ......\n\
+            .>..#.\n\
+            ......\n\
+            *.....\n",
+        )
+        .unwrap();
+        let config = SnakeConfig {
+            start_length: 2,
+            ..default()
+        };
+        let mut sim = SnakeSim::from_level(&config, &level, GameRng::from_seed(3));
+
+        assert_eq!(sim.snake(0).head().coords, Vec2::new(1.0, 2.0));
+        assert_eq!(sim.snake(0).segments()[1].coords, Vec2::new(0.0, 2.0));
+        assert!(!sim.add_food(Vec2::new(4.0, 2.0)));
+
+        let outcomes = replay(&mut sim, &[None, None, None]);
+
+        assert_eq!(
+            outcomes[0].spawned.map(|food| food.coords),
+            Some(Vec2::new(0.0, 0.0))
+        );
+        assert_eq!(outcomes[1].status, SimStatus::Running);
+        assert_eq!(outcomes[2].status, SimStatus::Lost);
+    }
+
+    fn tiny_board() -> SnakeConfig {
+        SnakeConfig {
+            board_size: (3, 3),
+            start_length: 2,
+            max_food: 1,
+            food_interval: 0.0,
+            food_weights: FoodWeights::CLASSIC,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn filling_a_tiny_board_is_a_perfect_game() {
+        let mut sim = SnakeSim::new(&tiny_board(), GameRng::from_seed(0));
+        // Turning north after every two cells east visits every cell of the wrapping board.
+        let cycle = [Some(Dir::N), Some(Dir::E), None];
+
+        let mut outcomes = vec![];
+        for input in cycle.iter().cycle().take(300) {
+            outcomes.push(sim.step(*input));
+            if sim.status() != SimStatus::Running {
+                break;
+            }
+        }
+
+        let (last, rest) = outcomes.split_last().unwrap();
+        assert_eq!(last.status, SimStatus::Won);
+        // The last move grows into the last free cell, and no food is spawned after it.
+        assert!(last.snakes[0].grew);
+        assert_eq!(last.spawned, None);
+        assert_eq!(sim.snake(0).len(), 9);
+        assert!(rest
+            .iter()
+            .all(|outcome| outcome.status == SimStatus::Running));
+        assert!(sim.is_perfect());
+        assert_eq!(sim.winner(), Some(0));
+    }
+
+    #[test]
+    fn covering_every_food_zone_keeps_the_game_running() {
+        let level = Level::parse(
+            "\
+            *..\n\
+            .>.\n\
+            ...\n",
+        )
+        .unwrap();
+        let mut sim = SnakeSim::from_level(&tiny_board(), &level, GameRng::from_seed(0));
+
+        let outcome = sim.step([Dir::N]);
+        assert_eq!(
+            outcome.spawned.map(|food| food.coords),
+            Some(Vec2::new(0.0, 2.0))
+        );
+
+        // Eating the food covers the only cell it can spawn on, until the snake moves off it.
+        let outcome = sim.step([Dir::W]);
+        assert!(outcome.snakes[0].eaten.is_some());
+        assert_eq!(outcome.status, SimStatus::Running);
+        assert_eq!(outcome.spawned, None);
+
+        let mut spawned = None;
+        for input in [Dir::S, Dir::S, Dir::E] {
+            let outcome = sim.step([input]);
+            assert_eq!(outcome.status, SimStatus::Running);
+            spawned = outcome.spawned.map(|food| food.coords);
+        }
+        assert_eq!(spawned, Some(Vec2::new(0.0, 2.0)));
+        assert!(!sim.is_perfect());
+        assert_eq!(sim.winner(), None);
+    }
+
+    fn two_players(level: &str) -> SnakeSim {
+        let config = SnakeConfig {
+            players: 2,
+            start_length: 3,
+            max_food: 0,
+            ..default()
+        };
+        SnakeSim::from_level(
+            &config,
+            &Level::parse(level).unwrap(),
+            GameRng::from_seed(0),
+        )
+    }
+
+    #[test]
+    fn head_on_collision_is_a_draw() {
+        let mut sim = two_players("...>.<...\n");
+
+        let outcome = sim.step_queued();
+
+        assert!(outcome.snakes.iter().all(|snake| snake.died));
+        assert_eq!(outcome.status, SimStatus::Lost);
+        assert_eq!(sim.winner(), None);
+    }
+
+    #[test]
+    fn running_into_another_snake_loses_the_round() {
+        let mut sim = two_players(
+            "\
+            .......\n\
+            ...>...\n\
+            ..^....\n\
+            .......\n\
+            .......\n",
+        );
+
+        let outcome = sim.step_queued();
+
+        assert!(!outcome.snakes[0].died);
+        assert!(outcome.snakes[1].died);
+        assert_eq!(outcome.status, SimStatus::Won);
+        assert_eq!(sim.winner(), Some(0));
+    }
+
+    #[test]
+    fn spawns_food_deterministically_from_seed() {
+        let moves = [None, Some(Dir::N), None, Some(Dir::W), None, None, None];
+        let run = |seed| {
+            let mut sim = SnakeSim::new(&default(), GameRng::from_seed(seed));
+            replay(&mut sim, &moves);
+            sim.food().to_vec()
+        };
+
+        assert!(!run(7).is_empty());
+        assert_eq!(run(7), run(7));
+    }
+
+    #[test]
+    fn shrink_food_takes_segments_off_the_tail() {
+        let mut sim = sim(15);
+        for x in 8..11 {
+            sim.add_food(Vec2::new(x as f32, 7.0));
+        }
+        replay(&mut sim, &[None; 7]);
+        assert_eq!(sim.snake(0).len(), 5);
+
+        assert!(sim.add_food_of(FoodKind::Shrink, Vec2::new(0.0, 7.0)));
+        let outcome = sim.step([]);
+
+        assert_eq!(outcome.snakes[0].eaten.unwrap().kind, FoodKind::Shrink);
+        assert_eq!(
+            sim.snake(0)
+                .segments()
+                .iter()
+                .map(|segment| segment.coords)
+                .collect::<Vec<_>>(),
+            [
+                Vec2::new(0.0, 7.0),
+                Vec2::new(14.0, 7.0),
+                Vec2::new(13.0, 7.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn ghost_food_lets_the_snake_pass_through_itself() {
+        let mut sim = sim(15);
+        for x in 8..11 {
+            sim.add_food(Vec2::new(x as f32, 7.0));
+        }
+        sim.add_food_of(FoodKind::Ghost, Vec2::new(11.0, 7.0));
+        replay(&mut sim, &[None; 7]);
+        assert!(sim.snake(0).is_ghost());
+
+        let outcomes = replay(&mut sim, &[Some(Dir::N), Some(Dir::W), Some(Dir::S)]);
+
+        assert!(outcomes
+            .iter()
+            .all(|outcome| outcome.status == SimStatus::Running));
+    }
+
+    #[test]
+    fn special_food_and_effects_wear_off() {
+        let config = SnakeConfig {
+            speed_curve: SpeedCurve::Constant,
+            special_food_lifetime: 1.0,
+            effect_duration: 0.5,
+            ..config(15)
+        };
+        let mut sim = SnakeSim::new(&config, GameRng::from_seed(0));
+        sim.add_food_of(FoodKind::SpeedUp, Vec2::new(8.0, 7.0));
+        sim.add_food_of(FoodKind::Golden, Vec2::new(7.0, 12.0));
+
+        sim.step([]);
+        assert_eq!(sim.speed_effect(), Some(FoodKind::SpeedUp));
+        assert!(sim.tick_duration() < config.tick_duration());
+
+        replay(&mut sim, &[None; 3]);
+        assert_eq!(sim.food().len(), 1);
+
+        replay(&mut sim, &[None; 4]);
+        assert_eq!(sim.speed_effect(), None);
+        assert_eq!(sim.tick_duration(), config.tick_duration());
+        assert!(sim.food().is_empty());
+    }
+
+    #[test]
+    fn occupancy_grid_keeps_up_with_the_game() {
+        let config = SnakeConfig {
+            players: 2,
+            controllers: vec![ControllerKind::Greedy; 2],
+            max_food: 5,
+            food_interval: 0.0,
+            ..config(12)
+        };
+
+        for seed in 0..5 {
+            let mut sim = SnakeSim::new(&config, GameRng::from_seed(seed));
+            let mut controllers = (0..config.players)
+                .map(|player| config.controller(player).build().unwrap())
+                .collect::<Vec<_>>();
+
+            while sim.status() == SimStatus::Running && sim.ticks() < 500 {
+                for (player, controller) in controllers.iter_mut().enumerate() {
+                    if let Some(dir) = controller.next_dir(&BoardView::new(&sim, player)) {
+                        sim.queue_input(player, dir);
+                    }
+                }
+                sim.step_queued();
+
+                let mut rebuilt = OccupancyGrid::new(sim.board_size(), sim.walls(), &[]);
+                for (player, snake) in sim.snakes().iter().enumerate() {
+                    for segment in snake.segments() {
+                        rebuilt.add_segment(segment.coords, player);
+                    }
+                }
+                for food in sim.food() {
+                    rebuilt.set_food(food.coords, true);
+                }
+                assert!(
+                    sim.occupancy().same_cells(&rebuilt),
+                    "seed {} tick {}",
+                    seed,
+                    sim.ticks()
+                );
+            }
+        }
+    }
+}
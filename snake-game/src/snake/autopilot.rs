@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+
+use super::{input::InputQueue, Snake};
+use crate::{
+    ai::{BoardView, SnakeController},
+    sim::SnakeSim,
+};
+
+/// Steers a player's snake with a [`SnakeController`] instead of the keyboard.
+#[derive(Component)]
+pub struct Autopilot(pub Box<dyn SnakeController>);
+
+pub(super) fn drive_autopilot(
+    sim: Res<SnakeSim>,
+    mut snakes: Query<(&Snake, &mut Autopilot, &mut InputQueue)>,
+) {
+    for (snake, mut autopilot, mut input_queue) in snakes.iter_mut() {
+        if !sim.snake(snake.player).is_alive() {
+            continue;
+        }
+
+        if let Some(dir) = autopilot.0.next_dir(&BoardView::new(&sim, snake.player)) {
+            input_queue.0.push_back(dir);
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::f32;
 
-#[derive(Component, Clone, Copy)]
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
 pub struct SnakeSegment {
     pub coords: Vec2,
     pub direction: (Dir, Dir),
@@ -31,6 +32,11 @@ impl SnakeSegment {
         )
     }
 
+    /// Whether the snake turns on this segment.
+    pub fn is_corner(&self) -> bool {
+        self.direction.0 != self.direction.1
+    }
+
     pub fn make_body(from: Dir, to: Dir, coords: Vec2) -> (Self, SegmentType) {
         (
             Self {
@@ -43,14 +49,14 @@ impl SnakeSegment {
     }
 }
 
-#[derive(Component, Clone, Copy)]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SegmentType {
     Head,
     Body,
     Tail,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Dir {
     N,
     E,
@@ -59,6 +65,16 @@ pub enum Dir {
 }
 
 impl Dir {
+    /// The direction after turning left.
+    pub fn left(self) -> Self {
+        match self {
+            Dir::N => Dir::W,
+            Dir::E => Dir::N,
+            Dir::S => Dir::E,
+            Dir::W => Dir::S,
+        }
+    }
+
     pub fn is_parallel(&self, other: Self) -> bool {
         match self {
             Dir::N | Dir::S => matches!(other, Dir::N | Dir::S),
@@ -67,9 +83,9 @@ impl Dir {
     }
 }
 
-impl Into<Quat> for Dir {
-    fn into(self) -> Quat {
-        Quat::from_rotation_z(match self {
+impl From<Dir> for Quat {
+    fn from(dir: Dir) -> Self {
+        Quat::from_rotation_z(match dir {
             Dir::N => f32::consts::FRAC_PI_2,
             Dir::E => 0.0,
             Dir::S => 3.0 * f32::consts::FRAC_PI_2,
@@ -78,9 +94,9 @@ impl Into<Quat> for Dir {
     }
 }
 
-impl Into<Vec2> for Dir {
-    fn into(self) -> Vec2 {
-        match self {
+impl From<Dir> for Vec2 {
+    fn from(dir: Dir) -> Self {
+        match dir {
             Dir::N => Vec2::new(0.0, 1.0),
             Dir::E => Vec2::new(1.0, 0.0),
             Dir::S => Vec2::new(0.0, -1.0),
@@ -90,4 +106,4 @@ impl Into<Vec2> for Dir {
 }
 
 #[derive(Component)]
-pub struct Anticipating;
\ No newline at end of file
+pub struct Anticipating;
@@ -1,31 +1,248 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, io};
 
 use bevy::prelude::*;
+use keymap::{ActionInput, ActionMap, Binding, HeldSticks, Rebindable, Swipe, SwipeTracker};
+use serde::{Deserialize, Serialize};
 
-use super::components;
+use super::{components::Dir, replay::Replay, Snake};
+use crate::{config::MAX_PLAYERS, game::GameState, gametick::run_game_ticks};
+
+pub const KEYMAP_PATH: &str = "snake_keymap.ron";
 
 pub struct InputPlugin;
 
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<InputQueue>()
-            .add_systems(Update, handle_input);
+        let keymap = keymap::load(KEYMAP_PATH).unwrap_or_else(|err| {
+            if err.kind() != io::ErrorKind::NotFound {
+                warn!("Could not load keymap from {}: {}", KEYMAP_PATH, err);
+            }
+            Keymap::default()
+        });
+
+        app.insert_resource(keymap).add_systems(
+            Update,
+            handle_input
+                .before(run_game_ticks)
+                .run_if(in_state(GameState::InGame))
+                .run_if(not(resource_exists::<Replay>)),
+        );
     }
 }
 
-fn handle_input(mut input_queue: ResMut<InputQueue>, keys: Res<ButtonInput<KeyCode>>) {
-    for key in keys.get_just_pressed() {
-        let dir = match key {
-            KeyCode::KeyA => components::Dir::W,
-            KeyCode::KeyW => components::Dir::N,
-            KeyCode::KeyS => components::Dir::S,
-            KeyCode::KeyD => components::Dir::E,
-            _ => continue,
+fn handle_input(
+    mut players: Query<(&Snake, &ActionMap<Action>, &mut HeldSticks, &mut InputQueue)>,
+    input: ActionInput,
+    touches: Res<Touches>,
+    mut swipe_tracker: Local<SwipeTracker>,
+) {
+    // Keys and the touch screen are shared, gamepads are handed out one per human player.
+    let swipes = swipe_tracker.swipes(&touches);
+    let gamepads = input.gamepads();
+
+    let mut players = players.iter_mut().collect::<Vec<_>>();
+    players.sort_by_key(|(snake, ..)| snake.player);
+    let is_single_player = players.len() == 1;
+
+    for (human, (_, action_map, held_sticks, input_queue)) in players.iter_mut().enumerate() {
+        let own_gamepads = match is_single_player {
+            true => gamepads.as_slice(),
+            false => gamepads.get(human..=human).unwrap_or_default(),
         };
 
-        input_queue.0.push_back(dir);
+        let triggered = input
+            .just_pressed(own_gamepads)
+            .chain(held_sticks.just_pushed(&input, action_map, own_gamepads))
+            .chain(swipes.iter().map(|&swipe| Binding::Swipe(swipe)));
+
+        for binding in triggered {
+            if let Some(action) = action_map.action(binding) {
+                input_queue.0.push_back(action.dir());
+            }
+        }
+    }
+}
+
+/// Directions a player has pressed since the last tick, handed to
+/// [`SnakeSim::queue_input`](crate::sim::SnakeSim::queue_input) which decides what to keep.
+#[derive(Component, Default)]
+pub struct InputQueue(pub VecDeque<Dir>);
+
+/// Something a player can tell their snake to do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Action {
+    pub const ALL: [Self; 4] = [Action::Up, Action::Down, Action::Left, Action::Right];
+
+    pub fn dir(self) -> Dir {
+        match self {
+            Action::Up => Dir::N,
+            Action::Down => Dir::S,
+            Action::Left => Dir::W,
+            Action::Right => Dir::E,
+        }
+    }
+}
+
+/// Everyone's bindings, saved to [`KEYMAP_PATH`] whenever they are changed.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keymap {
+    /// Bindings of each player in a multiplayer game.
+    pub players: Vec<ActionMap<Action>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            players: (0..MAX_PLAYERS).map(default_action_map).collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// The bindings that steer one player's snake. Someone playing alone can use everyone's.
+    pub fn action_map(&self, player: usize, players: usize) -> ActionMap<Action> {
+        match players {
+            1 => self
+                .players
+                .iter()
+                .fold(ActionMap::default(), |mut action_map, other| {
+                    action_map.merge(other);
+                    action_map
+                }),
+            _ => self
+                .players
+                .get(player)
+                .cloned()
+                .unwrap_or_else(|| default_action_map(player)),
+        }
+    }
+
+    /// A player's bindings, filling in defaults for players missing from the saved keymap.
+    pub fn player_mut(&mut self, player: usize) -> &mut ActionMap<Action> {
+        while self.players.len() <= player {
+            self.players.push(default_action_map(self.players.len()));
+        }
+        &mut self.players[player]
     }
 }
 
-#[derive(Resource, Default)]
-pub struct InputQueue(pub VecDeque<components::Dir>);
+impl Rebindable for Keymap {
+    type Action = Action;
+
+    fn bindings(&self, player: usize) -> Option<&ActionMap<Action>> {
+        self.players.get(player)
+    }
+
+    fn bindings_mut(&mut self, player: usize) -> Option<&mut ActionMap<Action>> {
+        Some(self.player_mut(player))
+    }
+
+    fn save(&self) {
+        if let Err(err) = keymap::save(self, KEYMAP_PATH) {
+            error!("Could not save keymap to {}: {}", KEYMAP_PATH, err);
+        }
+    }
+}
+
+/// The first player steers with WASD and the second with the arrow keys. Both can use a
+/// gamepad, and the first player can swipe.
+pub fn default_action_map(player: usize) -> ActionMap<Action> {
+    let mut action_map = ActionMap::default();
+
+    let [up, down, left, right] = match player {
+        0 => [KeyCode::KeyW, KeyCode::KeyS, KeyCode::KeyA, KeyCode::KeyD],
+        _ => [
+            KeyCode::ArrowUp,
+            KeyCode::ArrowDown,
+            KeyCode::ArrowLeft,
+            KeyCode::ArrowRight,
+        ],
+    };
+
+    action_map
+        .bind(Binding::Key(up), Action::Up)
+        .bind(Binding::Key(down), Action::Down)
+        .bind(Binding::Key(left), Action::Left)
+        .bind(Binding::Key(right), Action::Right)
+        .bind(
+            Binding::GamepadButton(GamepadButtonType::DPadUp),
+            Action::Up,
+        )
+        .bind(
+            Binding::GamepadButton(GamepadButtonType::DPadDown),
+            Action::Down,
+        )
+        .bind(
+            Binding::GamepadButton(GamepadButtonType::DPadLeft),
+            Action::Left,
+        )
+        .bind(
+            Binding::GamepadButton(GamepadButtonType::DPadRight),
+            Action::Right,
+        )
+        .bind(
+            Binding::GamepadAxis(GamepadAxisType::LeftStickY, true),
+            Action::Up,
+        )
+        .bind(
+            Binding::GamepadAxis(GamepadAxisType::LeftStickY, false),
+            Action::Down,
+        )
+        .bind(
+            Binding::GamepadAxis(GamepadAxisType::LeftStickX, false),
+            Action::Left,
+        )
+        .bind(
+            Binding::GamepadAxis(GamepadAxisType::LeftStickX, true),
+            Action::Right,
+        );
+
+    if player == 0 {
+        action_map
+            .bind(Binding::Swipe(Swipe::Up), Action::Up)
+            .bind(Binding::Swipe(Swipe::Down), Action::Down)
+            .bind(Binding::Swipe(Swipe::Left), Action::Left)
+            .bind(Binding::Swipe(Swipe::Right), Action::Right);
+    }
+
+    action_map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn players_get_their_own_keys_unless_alone() {
+        let keymap = Keymap::default();
+        let first = keymap.action_map(0, 2);
+        let second = keymap.action_map(1, 2);
+        let alone = keymap.action_map(0, 1);
+
+        assert_eq!(
+            first.action(Binding::Key(KeyCode::KeyA)),
+            Some(Action::Left)
+        );
+        assert_eq!(first.action(Binding::Key(KeyCode::ArrowLeft)), None);
+        assert_eq!(
+            second.action(Binding::Key(KeyCode::ArrowLeft)),
+            Some(Action::Left)
+        );
+        assert_eq!(second.action(Binding::Swipe(Swipe::Left)), None);
+
+        for key in [KeyCode::KeyA, KeyCode::ArrowLeft] {
+            assert_eq!(alone.action(Binding::Key(key)), Some(Action::Left));
+        }
+        assert_eq!(
+            alone.action(Binding::Swipe(Swipe::Left)),
+            Some(Action::Left)
+        );
+    }
+}
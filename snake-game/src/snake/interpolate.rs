@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use super::{components::SnakeSegment, GRID_SIZE};
+use crate::{
+    config::SnakeConfig,
+    gametick::GameTicker,
+    sim::{BoundaryMode, SimStatus, SnakeSim},
+    utils::coords_to_translation,
+};
+
+/// Where a segment was before the last tick, so it can be tweened towards where it is now.
+#[derive(Component, Clone, Copy)]
+pub struct PreviousSegment(pub SnakeSegment);
+
+/// Slides the segments that moved from their previous cell to their current one over the
+/// course of a tick.
+pub(super) fn interpolate_snake(
+    config: Res<SnakeConfig>,
+    game_ticker: Res<GameTicker>,
+    sim: Res<SnakeSim>,
+    mut segments: Query<(&SnakeSegment, &PreviousSegment, &mut Transform)>,
+) {
+    // Once the game has ended the snake stays where the last tick left it.
+    if !config.interpolate || sim.status() != SimStatus::Running {
+        return;
+    }
+
+    let board_size = sim.board_size();
+    let progress = game_ticker.fraction();
+
+    for (segment, previous, mut transform) in segments.iter_mut() {
+        // Body segments stay where they are, and were already drawn there when they changed,
+        // so only the head and tail are touched.
+        if previous.0 == *segment {
+            continue;
+        }
+
+        let coords = tween_coords(
+            previous.0.coords,
+            segment.coords,
+            progress,
+            board_size,
+            sim.config().boundary_mode,
+        );
+
+        transform.set_if_neq(
+            Transform::from_translation(coords_to_translation(
+                board_size,
+                Vec2::splat(GRID_SIZE),
+                coords,
+            ))
+            .with_rotation(
+                Quat::from(previous.0.direction.0).slerp(segment.direction.0.into(), progress),
+            ),
+        );
+    }
+}
+
+/// Cell position a segment is drawn at, `progress` of the way from one cell to the next.
+fn tween_coords(
+    from: Vec2,
+    to: Vec2,
+    progress: f32,
+    board_size: Vec2,
+    boundary_mode: BoundaryMode,
+) -> Vec2 {
+    match boundary_mode {
+        BoundaryMode::Wrap => {
+            // A segment crossing a wrapping edge takes the short way through the seam,
+            // and switches sides halfway through rather than leaving the board.
+            let mut delta = to - from;
+            delta -= board_size * (delta / board_size).round();
+            (from + delta * progress + 0.5).rem_euclid(board_size) - 0.5
+        }
+        BoundaryMode::Solid => from.lerp(to, progress),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tweens_through_the_wrapping_seam() {
+        let board_size = Vec2::splat(5.0);
+        let tween = |progress| {
+            tween_coords(
+                Vec2::new(4.0, 2.0),
+                Vec2::new(0.0, 2.0),
+                progress,
+                board_size,
+                BoundaryMode::Wrap,
+            )
+        };
+
+        assert_eq!(tween(0.0), Vec2::new(4.0, 2.0));
+        assert_eq!(tween(0.25), Vec2::new(4.25, 2.0));
+        assert_eq!(tween(0.75), Vec2::new(-0.25, 2.0));
+        assert_eq!(tween(1.0), Vec2::new(0.0, 2.0));
+
+        let solid = tween_coords(
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 2.0),
+            0.5,
+            board_size,
+            BoundaryMode::Solid,
+        );
+        assert_eq!(solid, Vec2::new(1.0, 1.5));
+    }
+}
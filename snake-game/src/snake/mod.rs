@@ -1,285 +1,513 @@
-use bevy::prelude::*;
-use components::{Anticipating, Dir, SegmentType, SnakeSegment};
-use input::{InputPlugin, InputQueue};
-use std::f32;
+use autopilot::Autopilot;
+use bevy::{ecs::query::QueryItem, prelude::*};
+use components::{Anticipating, SegmentType, SnakeSegment};
+use input::{InputPlugin, InputQueue, Keymap};
+use interpolate::PreviousSegment;
+use keymap::HeldSticks;
+use replay::{ReplayPlugin, ReplayRecorder};
+use std::{collections::VecDeque, f32};
 
 use crate::{
-    food::{Food, FoodTimer},
-    game::{self, BoardSize, GameState},
-    gametick::game_tick_finished,
+    config::{SnakeConfig, MAX_PLAYERS},
+    game::{self, GameState, Restart, RestartSet},
+    gametick::{run_game_ticks, GameTick, GameTickSet, GameTicker},
+    level::ActiveLevel,
+    rng::GameRng,
+    score::Score,
+    sim::{SimStatus, SnakeSim, StepOutcome},
+    theme::{theme_changed, ActiveTheme, SnakeSprites, Theme},
     utils::coords_to_translation,
 };
 
+pub mod autopilot;
 pub mod components;
-mod input;
+pub mod input;
+mod interpolate;
+pub mod replay;
 
-const IMAGE_SIZE: f32 = 20.0;
 pub const GRID_SIZE: f32 = 16.0;
+/// Tints the snake sprites so players can tell their snakes apart.
+const PLAYER_COLORS: [Color; MAX_PLAYERS] = [Color::WHITE, Color::srgb(0.5, 0.75, 1.0)];
+const GHOST_ALPHA: f32 = 0.4;
 
 pub struct SnakePlugin;
 
 impl Plugin for SnakePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(InputPlugin)
-            .add_systems(PreStartup, load_assets)
+        app.add_plugins((InputPlugin, ReplayPlugin))
+            .add_event::<StepOutcome>()
             .add_systems(Startup, (setup_snake, render_snake).chain())
+            .add_systems(Restart, despawn_snake.in_set(RestartSet::Cleanup))
             .add_systems(
-                Update,
-                (advance_snake, handle_eat, handle_collision, render_snake)
+                Restart,
+                (setup_snake, render_snake)
                     .chain()
-                    .run_if(in_state(GameState::InGame))
-                    .run_if(game_tick_finished),
+                    .in_set(RestartSet::Setup),
+            )
+            .add_systems(
+                GameTick,
+                (
+                    replay::play_replay
+                        .run_if(resource_exists::<replay::Replay>)
+                        .in_set(GameTickSet::Input),
+                    autopilot::drive_autopilot
+                        .run_if(not(resource_exists::<replay::Replay>))
+                        .in_set(GameTickSet::Input),
+                    (advance_snake, speed_up)
+                        .chain()
+                        .in_set(GameTickSet::Advance),
+                    handle_eat.in_set(GameTickSet::Eat),
+                    handle_collision.in_set(GameTickSet::Collide),
+                    render_snake.in_set(GameTickSet::Render),
+                ),
+            )
+            .add_systems(PostUpdate, redraw_snake.run_if(theme_changed))
+            .add_systems(
+                Update,
+                interpolate::interpolate_snake
+                    .after(run_game_ticks)
+                    .run_if(in_state(GameState::InGame)),
             );
     }
 }
 
-fn load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let suffix = "_20x20_C";
-    let head = asset_server.load(format!("head{}.png", suffix));
-    let body = asset_server.load(format!("body{}.png", suffix));
-    let tail = asset_server.load(format!("tail{}.png", suffix));
-
-    let suffix = "_20x20_bloated";
-    let head_bloated = asset_server.load(format!("head{}.png", suffix));
-    let body_bloated = asset_server.load(format!("body{}.png", suffix));
-    let tail_bloated = asset_server.load(format!("tail{}.png", suffix));
-
-    let suffix = "_20x20_anticipate";
-    let head_anticipate = asset_server.load(format!("head{}.png", suffix));
-
-    commands.insert_resource(SnakeTextures {
-        head,
-        body,
-        tail,
-        head_bloated,
-        body_bloated,
-        tail_bloated,
-        head_anticipate,
-    });
-}
-
-fn setup_snake(mut commands: Commands, board_size: Res<BoardSize>) {
-    let center = (board_size.0 / 2.0).floor();
-    let snake_segments = vec![
-        (SnakeSegment::make_head(Dir::E, center)),
-        (SnakeSegment::make_tail(Dir::E, center - Vec2::X)),
-    ];
-
-    let mut head_id = None;
-    let mut body_ids = vec![];
-    let mut tail_id = None;
-
-    for (segment, tp) in snake_segments {
-        let id = commands.spawn((segment, tp)).id();
-
-        match tp {
-            SegmentType::Head => head_id = Some(id),
-            SegmentType::Body => body_ids.push(id),
-            SegmentType::Tail => tail_id = Some(id),
+fn setup_snake(
+    mut commands: Commands,
+    config: Res<SnakeConfig>,
+    keymap: Res<Keymap>,
+    active_level: ActiveLevel,
+    game_rng: Res<GameRng>,
+) {
+    let sim = SnakeSim::from_level(&config, &active_level.get(), game_rng.clone());
+
+    for (player, sim_snake) in sim.snakes().iter().enumerate() {
+        let len = sim_snake.len();
+        let segment_ids = sim_snake
+            .segments()
+            .iter()
+            .enumerate()
+            .map(|(index, segment)| {
+                commands
+                    .spawn(segment_bundle(*segment, segment_type(index, len), player))
+                    .id()
+            })
+            .collect();
+
+        let mut player_entity = commands.spawn((
+            Snake {
+                player,
+                segment_ids,
+            },
+            InputQueue::default(),
+            Score::default(),
+        ));
+
+        match config.controller(player).build() {
+            Some(controller) => player_entity.insert(Autopilot(controller)),
+            None => player_entity.insert((
+                keymap.action_map(player, config.players),
+                HeldSticks::default(),
+            )),
         };
     }
 
-    let snake = Snake {
-        head_id: head_id.expect("Expected head segment"),
-        body_ids,
-        tail_id: tail_id.expect("Expected tail segment"),
-    };
+    info!(
+        "Starting snake game with seed {} (replay it with --seed {0})",
+        sim.seed()
+    );
+
+    commands.insert_resource(sim);
+}
 
-    commands.insert_resource(snake);
+#[allow(clippy::type_complexity)]
+fn despawn_snake(
+    mut commands: Commands,
+    entities: Query<Entity, Or<(With<SnakeSegment>, With<Snake>)>>,
+) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn();
+    }
 }
 
 fn advance_snake(
     mut commands: Commands,
-    mut snake: ResMut<Snake>,
-    mut segments: Query<&mut SnakeSegment>,
-    mut input_queue: ResMut<InputQueue>,
-    board_size: Res<BoardSize>,
+    mut sim: ResMut<SnakeSim>,
+    mut snakes: Query<(&mut Snake, &mut InputQueue)>,
+    mut segments: Query<(&mut SnakeSegment, &mut SegmentType, &mut PreviousSegment)>,
+    mut recorder: Option<ResMut<ReplayRecorder>>,
+    mut step_events: EventWriter<StepOutcome>,
 ) {
-    let mut head_segment = segments
-        .get_mut(snake.head_id)
-        .expect("Expected head segment");
-
-    let mut chosen_direction: Option<Dir> = None;
-    while let Some(direction) = input_queue.0.pop_front() {
-        if !direction.is_parallel(head_segment.direction.0) {
-            chosen_direction = Some(direction);
-            break;
+    for (snake, mut input_queue) in snakes.iter_mut() {
+        let inputs = input_queue.0.drain(..).collect::<Vec<_>>();
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record(sim.ticks(), snake.player, &inputs);
+        }
+
+        for dir in inputs {
+            sim.queue_input(snake.player, dir);
         }
     }
 
-    let direction = chosen_direction.unwrap_or(head_segment.direction.1);
+    let outcome = sim.step_queued();
 
-    let wrap_coords = |coords: Vec2| coords.rem_euclid(board_size.0);
+    for (mut snake, _) in snakes.iter_mut() {
+        let snake_outcome = outcome.snakes[snake.player];
+        if !snake_outcome.moved {
+            continue;
+        }
 
-    let mut front_segment = head_segment.clone();
-    front_segment.direction = (front_segment.direction.1, direction);
+        let sim_snake = sim.snake(snake.player);
+        let segment = |id| *segments.get(id).expect("Expected snake segment").0;
+        let (old_head, old_tail) = (segment(snake.head_id()), segment(snake.tail_id()));
+
+        // The tail entity is reused for the neck, unless the snake grew and its tail stayed put.
+        let neck_id = match snake_outcome.grew {
+            true => commands
+                .spawn(segment_bundle(
+                    sim_snake.segments()[1],
+                    SegmentType::Body,
+                    snake.player,
+                ))
+                .id(),
+            false => snake.segment_ids.pop_back().expect("Expected tail segment"),
+        };
+        snake.segment_ids.insert(1, neck_id);
+
+        // Shrinking takes segments off the tail.
+        while snake.len() > sim_snake.len() {
+            let tail_id = snake.segment_ids.pop_back().expect("Expected tail segment");
+            commands.entity(tail_id).despawn();
+        }
 
-    head_segment.coords = wrap_coords(head_segment.coords + Into::<Vec2>::into(direction));
-    head_segment.direction = (direction, direction);
-    head_segment.is_bloated = false;
+        let len = snake.len();
+        let mut mirror = |index: usize, previous| {
+            let id = snake.segment_ids[index];
+            // A neck spawned this tick already mirrors its segment.
+            if let Ok((mut current, mut tp, mut drawn_from)) = segments.get_mut(id) {
+                current.set_if_neq(sim_snake.segments()[index]);
+                tp.set_if_neq(segment_type(index, len));
+                drawn_from.0 = previous;
+            }
+        };
 
-    front_segment = snake
-        .body_ids
-        .iter_mut()
-        .fold(front_segment, |mut front_segment, body_id| {
-            let mut body_segment = segments.get_mut(*body_id).expect("Expected body segment");
+        // The head slides ahead, the neck appears where the head was, and the tail follows.
+        mirror(0, old_head);
+        mirror(1, sim_snake.segments()[1]);
+        mirror(len - 1, old_tail);
+    }
 
-            (front_segment, *body_segment) = (*body_segment, front_segment);
-            front_segment
-        });
+    step_events.send(outcome);
+}
 
-    let mut tail_segment = segments
-        .get_mut(snake.tail_id)
-        .expect("Expected tail segment");
+/// Components of an entity mirroring one of the sim's segments.
+fn segment_bundle(
+    segment: SnakeSegment,
+    tp: SegmentType,
+    player: usize,
+) -> (
+    SnakeSegment,
+    PreviousSegment,
+    SegmentType,
+    SnakeOwner,
+    SpatialBundle,
+) {
+    (
+        segment,
+        PreviousSegment(segment),
+        tp,
+        SnakeOwner(player),
+        SpatialBundle::default(),
+    )
+}
 
-    if tail_segment.is_bloated {
-        snake
-            .body_ids
-            .push(commands.spawn((front_segment, SegmentType::Body)).id());
-        tail_segment.is_bloated = false;
-    } else {
-        *tail_segment = front_segment;
+fn segment_type(index: usize, len: usize) -> SegmentType {
+    match index {
+        0 => SegmentType::Head,
+        index if index == len - 1 => SegmentType::Tail,
+        _ => SegmentType::Body,
     }
 }
 
 fn handle_eat(
     mut commands: Commands,
-    snake: Res<Snake>,
-    mut segments: Query<&mut SnakeSegment>,
-    foods: Query<(Entity, &Food)>,
-    mut food_timer: ResMut<FoodTimer>,
+    sim: Res<SnakeSim>,
+    mut snakes: Query<(&Snake, &mut Score)>,
+    heads: Query<Has<Anticipating>>,
+    mut step_events: EventReader<StepOutcome>,
 ) {
-    let mut head_segment = segments
-        .get_mut(snake.head_id)
-        .expect("Expected head segment");
-    if let Some((food_entity, _)) = foods
-        .iter()
-        .find(|(_, food)| food.coords == head_segment.coords)
-    {
-        commands
-            .get_entity(food_entity)
-            .expect("Expected food entity")
-            .despawn();
-
-        head_segment.is_bloated = true;
-        food_timer.0.reset();
+    for outcome in step_events.read() {
+        for (snake, mut score) in snakes.iter_mut() {
+            if let Some(eaten) = outcome.snakes[snake.player].eaten {
+                score.0 += eaten.kind.points();
+            }
+        }
     }
 
-    if foods
-        .iter()
-        .find(|(_, food)| {
-            food.coords == head_segment.coords + Into::<Vec2>::into(head_segment.direction.1)
-        })
-        .is_some()
-    {
-        commands
-            .get_entity(snake.head_id)
-            .expect("Expected head entity")
-            .insert(Anticipating);
-    } else {
-        commands
-            .get_entity(snake.head_id)
-            .expect("Expected head entity")
-            .remove::<Anticipating>();
+    // Only touched when it flips, so the head is not redrawn every tick.
+    for (snake, _) in snakes.iter() {
+        let was_anticipating = heads.get(snake.head_id()).expect("Expected head entity");
+
+        match (sim.is_anticipating(snake.player), was_anticipating) {
+            (true, false) => {
+                commands.entity(snake.head_id()).insert(Anticipating);
+            }
+            (false, true) => {
+                commands.entity(snake.head_id()).remove::<Anticipating>();
+            }
+            _ => {}
+        }
     }
 }
 
-fn handle_collision(
-    mut next_state: ResMut<NextState<game::GameState>>,
-    snake: Res<Snake>,
-    segments: Query<(Entity, &SnakeSegment)>,
-) {
-    let (head_entity, head_segment) = segments.get(snake.head_id).expect("Expected head segment");
+fn speed_up(sim: Res<SnakeSim>, mut game_ticker: ResMut<GameTicker>) {
+    if game_ticker.duration() != sim.tick_duration() {
+        game_ticker.set_duration(sim.tick_duration());
+    }
+}
 
-    if segments
-        .iter()
-        .find(|(entity, other)| {
-            other.coords == head_segment.coords && entity.index() != head_entity.index()
-        })
-        .is_some()
-    {
+fn handle_collision(mut next_state: ResMut<NextState<game::GameState>>, sim: Res<SnakeSim>) {
+    if sim.status() == SimStatus::Lost {
         next_state.set(game::GameState::GameOver);
     }
 }
 
+/// What a segment is drawn with.
+type SegmentLook = (
+    &'static SnakeSegment,
+    &'static SegmentType,
+    &'static SnakeOwner,
+    Has<Anticipating>,
+    &'static mut Transform,
+    Option<&'static mut Sprite>,
+    Option<&'static mut TextureAtlas>,
+);
+
+/// Redraws the segments that changed this tick, and every segment of a snake that became or
+/// stopped being a ghost.
+#[allow(clippy::type_complexity)]
 fn render_snake(
     mut commands: Commands,
-    snake_textures: Res<SnakeTextures>,
-    board_size: Res<BoardSize>,
-    mut segments: Query<(
+    theme: ActiveTheme,
+    sim: Res<SnakeSim>,
+    changed: Query<
         Entity,
-        &SnakeSegment,
-        &SegmentType,
-        Option<&mut Handle<Image>>,
-        Option<&Anticipating>,
-    )>,
+        (
+            With<SnakeSegment>,
+            Or<(
+                Changed<SnakeSegment>,
+                Changed<SegmentType>,
+                Changed<Anticipating>,
+            )>,
+        ),
+    >,
+    mut stopped_anticipating: RemovedComponents<Anticipating>,
+    mut segments: Query<(Entity, SegmentLook)>,
+    mut drawn_ghosts: Local<[bool; MAX_PLAYERS]>,
 ) {
-    for (entity, segment, tp, texture, anticipating) in segments.iter_mut() {
-        if let Some(mut texture) = texture {
-            if *texture != get_texture(tp, segment, &snake_textures, anticipating.is_some()) {
-                *texture = get_texture(tp, segment, &snake_textures, anticipating.is_some())
+    let mut redrawn = changed
+        .iter()
+        .chain(stopped_anticipating.read())
+        .collect::<Vec<_>>();
+
+    for (player, sim_snake) in sim.snakes().iter().enumerate() {
+        if drawn_ghosts[player] != sim_snake.is_ghost() {
+            drawn_ghosts[player] = sim_snake.is_ghost();
+            redrawn.extend(
+                segments
+                    .iter()
+                    .filter(|(_, (_, _, owner, ..))| owner.0 == player)
+                    .map(|(entity, _)| entity),
+            );
+        }
+    }
+
+    for entity in redrawn {
+        // Segments despawned since they changed are left alone.
+        if let Ok((entity, look)) = segments.get_mut(entity) {
+            draw_segment(&mut commands, theme.get(), &sim, entity, look);
+        }
+    }
+}
+
+/// Redraws every segment, once a new theme has loaded.
+fn redraw_snake(
+    mut commands: Commands,
+    theme: ActiveTheme,
+    sim: Res<SnakeSim>,
+    mut segments: Query<(Entity, SegmentLook)>,
+) {
+    for (entity, look) in segments.iter_mut() {
+        draw_segment(&mut commands, theme.get(), &sim, entity, look);
+    }
+}
+
+/// Moves a segment's sprite to its cell and picks its region of the sheet, changing only
+/// what differs from last time.
+fn draw_segment(
+    commands: &mut Commands,
+    theme: Option<&Theme>,
+    sim: &SnakeSim,
+    entity: Entity,
+    (segment, tp, owner, anticipating, mut transform, sprite, atlas): QueryItem<SegmentLook>,
+) {
+    transform.set_if_neq(
+        Transform::from_translation(coords_to_translation(
+            sim.board_size(),
+            Vec2::splat(GRID_SIZE),
+            segment.coords,
+        ))
+        .with_rotation(segment.direction.0.into()),
+    );
+
+    // Drawn once the theme has loaded.
+    let Some(theme) = theme else {
+        return;
+    };
+
+    // Ghosts are drawn see-through while they can pass through themselves.
+    let color = match sim.snake(owner.0).is_ghost() {
+        true => PLAYER_COLORS[owner.0].with_alpha(GHOST_ALPHA),
+        false => PLAYER_COLORS[owner.0],
+    };
+    let index = sprite_index(tp, segment, &theme.snake, anticipating);
+    let flip_y = is_right_turn(tp, segment);
+
+    match (sprite, atlas) {
+        (Some(mut sprite), Some(mut atlas)) if atlas.layout == theme.layout => {
+            if atlas.index != index {
+                atlas.index = index;
+            }
+            if sprite.color != color {
+                sprite.color = color;
             }
-        } else {
-            commands
-                .get_entity(entity)
-                .expect("Expected segment to already exist")
-                .insert(SpriteBundle {
-                    texture: get_texture(tp, segment, &snake_textures, anticipating.is_some()),
+            if sprite.flip_y != flip_y {
+                sprite.flip_y = flip_y;
+            }
+        }
+        _ => {
+            commands.entity(entity).insert(theme.sprite(
+                index,
+                Sprite {
+                    color,
+                    flip_y,
                     ..default()
-                });
+                },
+            ));
         }
-
-        commands
-            .get_entity(entity)
-            .expect("Expected segment to already exist")
-            .insert(
-                Transform::from_translation(coords_to_translation(
-                    board_size.0,
-                    Vec2::splat(GRID_SIZE),
-                    segment.coords,
-                ))
-                .with_rotation(segment.direction.0.into()),
-            );
     }
 }
 
-fn get_texture(
+fn sprite_index(
     tp: &SegmentType,
     segment: &SnakeSegment,
-    snake_textures: &Res<SnakeTextures>,
+    sprites: &SnakeSprites,
     anticipating: bool,
-) -> Handle<Image> {
+) -> usize {
     match (tp, segment.is_bloated, anticipating) {
-        (SegmentType::Head, true, _) => snake_textures.head_bloated.clone(),
-        (SegmentType::Head, false, true) => snake_textures.head_anticipate.clone(),
-        (SegmentType::Head, false, false) => snake_textures.head.clone(),
-        (SegmentType::Body, true, _) => snake_textures.body_bloated.clone(),
-        (SegmentType::Body, false, _) => snake_textures.body.clone(),
-        (SegmentType::Tail, true, _) => snake_textures.tail_bloated.clone(),
-        (SegmentType::Tail, false, _) => snake_textures.tail.clone(),
+        (SegmentType::Head, true, _) => sprites.head_bloated,
+        (SegmentType::Head, false, true) => sprites.head_anticipate,
+        (SegmentType::Head, false, false) => sprites.head,
+        (SegmentType::Body, true, _) if segment.is_corner() => sprites.body_corner_bloated,
+        (SegmentType::Body, false, _) if segment.is_corner() => sprites.body_corner,
+        (SegmentType::Body, true, _) => sprites.body_bloated,
+        (SegmentType::Body, false, _) => sprites.body,
+        (SegmentType::Tail, true, _) => sprites.tail_bloated,
+        (SegmentType::Tail, false, _) => sprites.tail,
     }
 }
 
-#[derive(Resource)]
-struct SnakeTextures {
-    head: Handle<Image>,
-    body: Handle<Image>,
-    tail: Handle<Image>,
-    head_bloated: Handle<Image>,
-    body_bloated: Handle<Image>,
-    tail_bloated: Handle<Image>,
-    head_anticipate: Handle<Image>,
+/// Corner sprites turn left, so right turns are drawn mirrored across the direction of travel.
+fn is_right_turn(tp: &SegmentType, segment: &SnakeSegment) -> bool {
+    matches!(tp, SegmentType::Body)
+        && segment.is_corner()
+        && segment.direction.1 != segment.direction.0.left()
 }
 
-#[derive(Resource)]
+/// One player's snake, holding the entities that mirror its segments in [`SnakeSim`].
+#[derive(Component)]
 pub struct Snake {
-    pub head_id: Entity,
-    pub body_ids: Vec<Entity>,
-    pub tail_id: Entity,
+    pub player: usize,
+    /// Head first and tail last, like [`SimSnake::segments`](crate::sim::SimSnake::segments).
+    /// A move turns the tail entity into the new neck, so the body entities never change.
+    segment_ids: VecDeque<Entity>,
 }
 
+/// The player whose snake a segment belongs to.
+#[derive(Component, Clone, Copy)]
+pub struct SnakeOwner(pub usize);
+
+#[allow(clippy::len_without_is_empty)]
 impl Snake {
     pub fn len(&self) -> usize {
-        self.body_ids.len() + 2
+        self.segment_ids.len()
+    }
+
+    pub fn head_id(&self) -> Entity {
+        self.segment_ids[0]
+    }
+
+    pub fn tail_id(&self) -> Entity {
+        self.segment_ids[self.segment_ids.len() - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use components::Dir;
+
+    #[test]
+    fn draws_every_turn_with_a_corner_mirrored_for_right_turns() {
+        let sprites = SnakeSprites {
+            head: 0,
+            head_bloated: 1,
+            head_anticipate: 2,
+            body: 3,
+            body_bloated: 4,
+            body_corner: 5,
+            body_corner_bloated: 6,
+            tail: 7,
+            tail_bloated: 8,
+        };
+        // The direction the snake came in moving, then the one it left in.
+        let turns = [
+            (Dir::E, Dir::N, false),
+            (Dir::N, Dir::W, false),
+            (Dir::W, Dir::S, false),
+            (Dir::S, Dir::E, false),
+            (Dir::E, Dir::S, true),
+            (Dir::S, Dir::W, true),
+            (Dir::W, Dir::N, true),
+            (Dir::N, Dir::E, true),
+        ];
+
+        for (from, to, is_right) in turns {
+            let (mut segment, tp) = SnakeSegment::make_body(from, to, Vec2::ZERO);
+
+            assert_eq!(
+                sprite_index(&tp, &segment, &sprites, false),
+                sprites.body_corner,
+                "{:?} to {:?}",
+                from,
+                to
+            );
+            assert_eq!(
+                is_right_turn(&tp, &segment),
+                is_right,
+                "{:?} to {:?}",
+                from,
+                to
+            );
+
+            segment.is_bloated = true;
+            assert_eq!(
+                sprite_index(&tp, &segment, &sprites, false),
+                sprites.body_corner_bloated
+            );
+        }
+
+        let (straight, tp) = SnakeSegment::make_body(Dir::E, Dir::E, Vec2::ZERO);
+        assert_eq!(sprite_index(&tp, &straight, &sprites, false), sprites.body);
+        assert!(!is_right_turn(&tp, &straight));
     }
 }
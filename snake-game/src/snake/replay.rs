@@ -0,0 +1,233 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{components::Dir, input::InputQueue, Snake};
+use crate::{
+    config::SnakeConfig,
+    game::{GameState, Restart, RestartSet},
+    rng::GameRng,
+    sim::SnakeSim,
+    utils::cli_arg,
+};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(path) = cli_arg("replay") {
+            match Replay::load(&path) {
+                // Overrides the seed and rules picked by `GamePlugin` so the game plays out
+                // exactly like the recording.
+                Ok(replay) => {
+                    app.insert_resource(GameRng::from_seed(replay.seed))
+                        .insert_resource(replay.config.clone())
+                        .insert_resource(replay);
+                }
+                Err(err) => error!("Could not load replay {}: {}", path, err),
+            }
+        }
+
+        if let Some(path) = cli_arg("record") {
+            app.insert_resource(ReplayRecorder {
+                path: path.into(),
+                inputs: vec![],
+            })
+            .add_systems(OnEnter(GameState::GameOver), save_recording)
+            .add_systems(OnEnter(GameState::GameWin), save_recording)
+            .add_systems(
+                Last,
+                save_recording
+                    .run_if(on_event::<AppExit>())
+                    .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
+            )
+            .add_systems(Restart, clear_recording.in_set(RestartSet::Cleanup));
+        }
+    }
+}
+
+/// A direction that was fed to a player's snake right before the given tick.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedInput {
+    pub tick: u64,
+    #[serde(default)]
+    pub player: usize,
+    pub dir: Dir,
+}
+
+/// Everything needed to play a game again: its seed, rules and inputs, sorted by tick.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    #[serde(default)]
+    pub config: SnakeConfig,
+    pub inputs: Vec<RecordedInput>,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Reads a replay, rejecting rules the game could not be played with, as replays are shared.
+    pub fn parse(replay: &str) -> io::Result<Self> {
+        let replay: Self =
+            ron::from_str(replay).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        replay
+            .config
+            .validate()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let replay = ron::ser::to_string_pretty(self, default())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        fs::write(path, replay)
+    }
+
+    /// Player and direction of every input fed right before the given tick.
+    pub fn inputs_at(&self, tick: u64) -> impl Iterator<Item = (usize, Dir)> + '_ {
+        let start = self.inputs.partition_point(|input| input.tick < tick);
+
+        self.inputs[start..]
+            .iter()
+            .take_while(move |input| input.tick == tick)
+            .map(|input| (input.player, input.dir))
+    }
+}
+
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    path: PathBuf,
+    inputs: Vec<RecordedInput>,
+}
+
+impl ReplayRecorder {
+    pub fn record(&mut self, tick: u64, player: usize, inputs: &[Dir]) {
+        self.inputs.extend(
+            inputs
+                .iter()
+                .map(|&dir| RecordedInput { tick, player, dir }),
+        );
+    }
+}
+
+pub(super) fn play_replay(
+    replay: Res<Replay>,
+    sim: Res<SnakeSim>,
+    mut snakes: Query<(&Snake, &mut InputQueue)>,
+) {
+    for (player, dir) in replay.inputs_at(sim.ticks()) {
+        if let Some((_, mut input_queue)) =
+            snakes.iter_mut().find(|(snake, _)| snake.player == player)
+        {
+            input_queue.0.push_back(dir);
+        }
+    }
+}
+
+fn clear_recording(mut recorder: ResMut<ReplayRecorder>) {
+    recorder.inputs.clear();
+}
+
+fn save_recording(recorder: Res<ReplayRecorder>, sim: Res<SnakeSim>) {
+    let replay = Replay {
+        seed: sim.seed(),
+        config: sim.config().clone(),
+        inputs: recorder.inputs.clone(),
+    };
+    let path = &recorder.path;
+
+    match replay.save(path) {
+        Ok(()) => info!("Saved replay to {}", path.display()),
+        Err(err) => error!("Could not save replay to {}: {}", path.display(), err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sim(config: &SnakeConfig, seed: u64) -> SnakeSim {
+        SnakeSim::new(config, GameRng::from_seed(seed))
+    }
+
+    #[test]
+    fn replays_recorded_game() {
+        let moves = [
+            vec![],
+            vec![(0, Dir::N), (1, Dir::S)],
+            vec![],
+            vec![(0, Dir::W), (0, Dir::S)],
+            vec![(1, Dir::E)],
+            vec![(0, Dir::E)],
+            vec![],
+            vec![],
+        ];
+
+        let config = SnakeConfig {
+            board_size: (12, 9),
+            players: 2,
+            ..default()
+        };
+        let mut recorded = sim(&config, 42);
+        let mut recorder = ReplayRecorder {
+            path: PathBuf::new(),
+            inputs: vec![],
+        };
+        for inputs in &moves {
+            for &(player, dir) in inputs {
+                recorder.record(recorded.ticks(), player, &[dir]);
+                recorded.queue_input(player, dir);
+            }
+            recorded.step_queued();
+        }
+
+        let replay = Replay {
+            seed: recorded.seed(),
+            config: recorded.config().clone(),
+            inputs: recorder.inputs,
+        };
+        let replay =
+            Replay::parse(&ron::ser::to_string_pretty(&replay, default()).unwrap()).unwrap();
+
+        let mut replayed = sim(&replay.config, replay.seed);
+        for _ in &moves {
+            for (player, dir) in replay.inputs_at(replayed.ticks()).collect::<Vec<_>>() {
+                replayed.queue_input(player, dir);
+            }
+            replayed.step_queued();
+        }
+
+        for player in 0..2 {
+            assert_eq!(
+                replayed.snake(player).segments(),
+                recorded.snake(player).segments()
+            );
+        }
+        assert_eq!(replayed.food(), recorded.food());
+    }
+
+    #[test]
+    fn rejects_replays_with_invalid_rules() {
+        for config in [
+            "(start_length: 0)",
+            "(players: 3)",
+            "(tick: 0.0)",
+            "(board_size: (0, 5))",
+        ] {
+            let err =
+                Replay::parse(&format!("(seed: 1, config: {}, inputs: [])", config)).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{}", config);
+        }
+
+        assert!(Replay::parse("(seed: 1, inputs: [])").is_ok());
+    }
+}
@@ -0,0 +1,298 @@
+use std::{error::Error, fmt, io};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{config::SnakeConfig, food::FoodKind};
+
+/// Themes offered in the settings menu. Any other theme can be picked with `--theme`.
+pub const THEMES: [&str; 2] = ["themes/classic.theme.ron", "themes/blocky.theme.ron"];
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Theme>()
+            .init_asset_loader::<ThemeLoader>()
+            .add_systems(PreStartup, load_theme)
+            .add_systems(PreUpdate, switch_theme);
+    }
+}
+
+/// A skin for the snake and food: one sprite sheet, and the region of it drawn for each state.
+#[derive(Asset, TypePath, Debug)]
+pub struct Theme {
+    pub sheet: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub snake: SnakeSprites,
+    pub food: FoodSprites,
+}
+
+impl Theme {
+    /// Components drawing the given region of the sheet, for an entity that already has a
+    /// [`SpatialBundle`].
+    pub fn sprite(&self, index: usize, sprite: Sprite) -> (Sprite, Handle<Image>, TextureAtlas) {
+        (
+            sprite,
+            self.sheet.clone(),
+            TextureAtlas {
+                layout: self.layout.clone(),
+                index,
+            },
+        )
+    }
+}
+
+/// Sheet regions for the snake, all facing east.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct SnakeSprites {
+    pub head: usize,
+    pub head_bloated: usize,
+    /// Head about to eat the food in front of it.
+    pub head_anticipate: usize,
+    pub body: usize,
+    pub body_bloated: usize,
+    /// Body turning left: in through the west edge, out through the north edge.
+    pub body_corner: usize,
+    pub body_corner_bloated: usize,
+    pub tail: usize,
+    pub tail_bloated: usize,
+}
+
+/// Sheet regions for each [`FoodKind`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct FoodSprites {
+    pub normal: usize,
+    pub golden: usize,
+    pub shrink: usize,
+    pub speed_up: usize,
+    pub slow_down: usize,
+    pub ghost: usize,
+}
+
+impl FoodSprites {
+    pub fn get(&self, kind: FoodKind) -> usize {
+        match kind {
+            FoodKind::Normal => self.normal,
+            FoodKind::Golden => self.golden,
+            FoodKind::Shrink => self.shrink,
+            FoodKind::SpeedUp => self.speed_up,
+            FoodKind::SlowDown => self.slow_down,
+            FoodKind::Ghost => self.ghost,
+        }
+    }
+}
+
+/// What a `.theme.ron` file holds. The sheet is cut into a grid of equally sized regions,
+/// numbered left to right and then top to bottom.
+#[derive(Deserialize)]
+struct ThemeDescriptor {
+    /// Path of the sprite sheet, relative to the theme file.
+    sheet: String,
+    tile_size: (u32, u32),
+    columns: u32,
+    rows: u32,
+    snake: SnakeSprites,
+    food: FoodSprites,
+}
+
+impl ThemeDescriptor {
+    fn parse(theme: &[u8]) -> Result<Self, ThemeError> {
+        let descriptor: Self = ron::de::from_bytes(theme).map_err(ThemeError::Parse)?;
+
+        let SnakeSprites {
+            head,
+            head_bloated,
+            head_anticipate,
+            body,
+            body_bloated,
+            body_corner,
+            body_corner_bloated,
+            tail,
+            tail_bloated,
+        } = descriptor.snake;
+        let regions = (descriptor.columns * descriptor.rows) as usize;
+
+        [
+            head,
+            head_bloated,
+            head_anticipate,
+            body,
+            body_bloated,
+            body_corner,
+            body_corner_bloated,
+            tail,
+            tail_bloated,
+        ]
+        .into_iter()
+        .chain(FoodKind::ALL.map(|kind| descriptor.food.get(kind)))
+        .find(|&index| index >= regions)
+        .map_or(Ok(descriptor), |index| {
+            Err(ThemeError::RegionOutOfSheet(index, regions))
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    InvalidSheetPath(String),
+    RegionOutOfSheet(usize, usize),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(err) => write!(f, "could not read theme: {}", err),
+            ThemeError::Parse(err) => write!(f, "could not parse theme: {}", err),
+            ThemeError::InvalidSheetPath(path) => write!(f, "invalid sheet path {:?}", path),
+            ThemeError::RegionOutOfSheet(index, regions) => write!(
+                f,
+                "region {} is outside the sheet, which has {} regions",
+                index, regions
+            ),
+        }
+    }
+}
+
+impl Error for ThemeError {}
+
+impl From<io::Error> for ThemeError {
+    fn from(err: io::Error) -> Self {
+        ThemeError::Io(err)
+    }
+}
+
+#[derive(Default)]
+struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    type Asset = Theme;
+    type Settings = ();
+    type Error = ThemeError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Theme, ThemeError> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        let descriptor = ThemeDescriptor::parse(&bytes)?;
+
+        let sheet_path = load_context
+            .asset_path()
+            .resolve_embed(&descriptor.sheet)
+            .map_err(|_| ThemeError::InvalidSheetPath(descriptor.sheet.clone()))?;
+        let layout = TextureAtlasLayout::from_grid(
+            UVec2::new(descriptor.tile_size.0, descriptor.tile_size.1),
+            descriptor.columns,
+            descriptor.rows,
+            None,
+            None,
+        );
+
+        Ok(Theme {
+            sheet: load_context.load(sheet_path),
+            layout: load_context.add_labeled_asset("layout".to_string(), layout),
+            snake: descriptor.snake,
+            food: descriptor.food,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+/// The theme picked in [`SnakeConfig::theme`].
+#[derive(Resource)]
+struct ThemeHandle {
+    path: String,
+    handle: Handle<Theme>,
+}
+
+/// The theme sprites are drawn with.
+#[derive(SystemParam)]
+pub struct ActiveTheme<'w> {
+    handle: Res<'w, ThemeHandle>,
+    themes: Res<'w, Assets<Theme>>,
+}
+
+impl ActiveTheme<'_> {
+    /// `None` while the theme is still loading, or if it failed to load.
+    pub fn get(&self) -> Option<&Theme> {
+        self.themes.get(&self.handle.handle)
+    }
+}
+
+fn load_theme(mut commands: Commands, config: Res<SnakeConfig>, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ThemeHandle {
+        path: config.theme.clone(),
+        handle: asset_server.load(config.theme.clone()),
+    });
+}
+
+/// Loads a different theme as soon as it is picked in the settings.
+fn switch_theme(
+    config: Res<SnakeConfig>,
+    mut theme_handle: ResMut<ThemeHandle>,
+    asset_server: Res<AssetServer>,
+) {
+    if config.is_changed() && config.theme != theme_handle.path {
+        *theme_handle = ThemeHandle {
+            path: config.theme.clone(),
+            handle: asset_server.load(config.theme.clone()),
+        };
+    }
+}
+
+/// Run condition for redrawing sprites once a new theme has loaded, or the current one changed on disk.
+pub fn theme_changed(
+    mut theme_events: EventReader<AssetEvent<Theme>>,
+    active_theme: ActiveTheme,
+) -> bool {
+    let id = active_theme.handle.handle.id();
+
+    // Every event has to be read, so they are not seen again next frame.
+    let is_loaded = theme_events
+        .read()
+        .filter(|event| event.is_loaded_with_dependencies(id) || event.is_modified(id))
+        .count()
+        > 0;
+
+    // Switching back to a theme that is already loaded sends no event.
+    is_loaded || active_theme.handle.is_changed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bundled_themes() {
+        for path in THEMES {
+            let theme = std::fs::read(format!("assets/{}", path)).unwrap();
+            assert!(ThemeDescriptor::parse(&theme).is_ok(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn rejects_regions_outside_the_sheet() {
+        let theme = std::fs::read("assets/themes/classic.theme.ron").unwrap();
+        let theme = String::from_utf8(theme)
+            .unwrap()
+            .replace("rows: 2", "rows: 1");
+
+        assert!(matches!(
+            ThemeDescriptor::parse(theme.as_bytes()),
+            Err(ThemeError::RegionOutOfSheet(8, 8))
+        ));
+    }
+}
@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+use keymap::{BindingsLabel, RebindPlugin, RebindSet, Rebindable, Rebinding};
+
+use super::{label, spawn_button, spawn_overlay, title, MenuAction};
+use crate::{
+    config::MAX_PLAYERS,
+    game::GameState,
+    snake::input::{Action, Keymap},
+};
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RebindPlugin::<Keymap, _>::new(GameState::Controls))
+            .add_systems(OnEnter(GameState::Controls), spawn_controls)
+            .add_systems(
+                Update,
+                change_controls
+                    .before(RebindSet)
+                    .run_if(in_state(GameState::Controls)),
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+enum ControlButton {
+    Rebind(usize, Action),
+    /// Removes every binding of the action.
+    Clear(usize, Action),
+    /// Restores everyone's default bindings.
+    Reset,
+}
+
+fn spawn_controls(mut commands: Commands) {
+    let overlay = spawn_overlay(&mut commands, GameState::Controls);
+    commands.entity(overlay).with_children(|parent| {
+        parent.spawn(title("Controls"));
+
+        for player in 0..MAX_PLAYERS {
+            parent.spawn(label(format!("Player {}", player + 1)));
+
+            for action in Action::ALL {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(8.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn(label(format!("{:?}", action)));
+                        row.spawn((BindingsLabel { player, action }, label("")));
+                        spawn_button(row, "Rebind", ControlButton::Rebind(player, action));
+                        spawn_button(row, "Clear", ControlButton::Clear(player, action));
+                    });
+            }
+        }
+
+        parent.spawn(label(
+            "Press a key or gamepad button after clicking Rebind, or Escape to cancel",
+        ));
+        spawn_button(parent, "Reset", ControlButton::Reset);
+        spawn_button(parent, "Back", MenuAction::MainMenu);
+    });
+}
+
+fn change_controls(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &ControlButton), Changed<Interaction>>,
+    mut keymap: ResMut<Keymap>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *button {
+            ControlButton::Rebind(player, action) => {
+                commands.insert_resource(Rebinding { player, action });
+                continue;
+            }
+            ControlButton::Clear(player, action) => {
+                keymap.player_mut(player).clear(action);
+            }
+            ControlButton::Reset => *keymap = default(),
+        }
+
+        keymap.save();
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+use super::{label, spawn_button, spawn_overlay, title, MenuAction};
+use crate::{
+    ai::ControllerKind,
+    game::GameState,
+    score::{HighScore, HighScores, Score, HIGH_SCORES_PATH},
+    sim::SnakeSim,
+    snake::{replay::Replay, Snake},
+};
+
+pub struct EndScreenPlugin;
+
+impl Plugin for EndScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::GameWin), spawn_end_screen)
+            .add_systems(OnEnter(GameState::GameOver), spawn_end_screen)
+            .add_systems(OnExit(GameState::GameWin), discard_name_entry)
+            .add_systems(OnExit(GameState::GameOver), discard_name_entry)
+            .add_systems(
+                Update,
+                (
+                    enter_name.run_if(resource_exists::<NameEntry>),
+                    handle_restart.run_if(not(resource_exists::<NameEntry>)),
+                )
+                    .run_if(in_state(GameState::GameWin).or_else(in_state(GameState::GameOver))),
+            );
+    }
+}
+
+const RESTART_KEY: KeyCode = KeyCode::KeyR;
+const MAX_NAME_LENGTH: usize = 12;
+
+/// Name being typed for a new high score.
+#[derive(Resource, Default)]
+struct NameEntry(String);
+
+#[derive(Component)]
+struct NameEntryText;
+
+fn spawn_end_screen(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    sim: Res<SnakeSim>,
+    players: Query<(&Snake, &Score)>,
+    high_scores: Res<HighScores>,
+    replay: Option<Res<Replay>>,
+) {
+    let mut players = players.iter().collect::<Vec<_>>();
+    players.sort_by_key(|(snake, _)| snake.player);
+    let is_multiplayer = players.len() > 1;
+
+    let heading = match (state.get(), sim.winner()) {
+        (GameState::GameWin, Some(winner)) if is_multiplayer => {
+            format!("Player {} Wins!", winner + 1)
+        }
+        (GameState::GameWin, _) if sim.is_perfect() => "Perfect Game!".to_string(),
+        (GameState::GameWin, _) => "You Win!".to_string(),
+        _ if is_multiplayer => "Draw".to_string(),
+        _ => "Game Over".to_string(),
+    };
+    // High scores are only kept for single player games a person played themselves, and a
+    // replay's score was already offered when it was recorded.
+    let is_high_score = !is_multiplayer
+        && replay.is_none()
+        && sim.config().controller(0) == ControllerKind::Keyboard
+        && players.first().is_some_and(|(_, score)| {
+            high_scores.qualifies(sim.board_size(), sim.config().boundary_mode, score.0)
+        });
+
+    let overlay = spawn_overlay(&mut commands, state.get().clone());
+    commands.entity(overlay).with_children(|parent| {
+        parent.spawn(title(heading));
+        for (snake, score) in &players {
+            let length = sim.snake(snake.player).len();
+            parent.spawn(label(match is_multiplayer {
+                false => format!("Score: {}  Length: {}", score.0, length),
+                true => format!(
+                    "Player {}  Score: {}  Length: {}",
+                    snake.player + 1,
+                    score.0,
+                    length
+                ),
+            }));
+        }
+        parent.spawn(label(format!(
+            "Time survived: {:.1}s",
+            sim.elapsed().as_secs_f32()
+        )));
+        parent.spawn(label(format!("Seed: {}", sim.seed())));
+
+        if is_high_score {
+            parent.spawn(label("New high score! Type your name and press Enter"));
+            parent.spawn((NameEntryText, label("Name: _")));
+        }
+
+        spawn_button(parent, "Restart (R)", MenuAction::Play);
+        spawn_button(parent, "Main Menu", MenuAction::MainMenu);
+    });
+
+    if is_high_score {
+        commands.init_resource::<NameEntry>();
+    }
+}
+
+fn enter_name(
+    mut commands: Commands,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut name_entry: ResMut<NameEntry>,
+    mut name_text: Query<&mut Text, With<NameEntryText>>,
+    mut high_scores: ResMut<HighScores>,
+    players: Query<(&Snake, &Score)>,
+    sim: Res<SnakeSim>,
+) {
+    // Keys still held from the last moves of the game are not part of the name.
+    if name_entry.is_added() {
+        keyboard_events.clear();
+        return;
+    }
+
+    let Ok(mut text) = name_text.get_single_mut() else {
+        return;
+    };
+
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::Character(characters) => {
+                for character in characters.chars() {
+                    if name_entry.0.len() < MAX_NAME_LENGTH
+                        && (character.is_alphanumeric() || character == ' ')
+                    {
+                        name_entry.0.push(character);
+                    }
+                }
+            }
+            Key::Space if name_entry.0.len() < MAX_NAME_LENGTH => name_entry.0.push(' '),
+            Key::Backspace => {
+                name_entry.0.pop();
+            }
+            Key::Enter if !name_entry.0.trim().is_empty() => {
+                let Some((snake, score)) = players.iter().find(|(snake, _)| snake.player == 0)
+                else {
+                    continue;
+                };
+
+                high_scores.insert(
+                    sim.board_size(),
+                    sim.config().boundary_mode,
+                    HighScore {
+                        name: name_entry.0.trim().to_string(),
+                        score: score.0,
+                        length: sim.snake(snake.player).len(),
+                    },
+                );
+
+                if let Err(err) = high_scores.save(HIGH_SCORES_PATH) {
+                    error!(
+                        "Could not save high scores to {}: {}",
+                        HIGH_SCORES_PATH, err
+                    );
+                }
+
+                text.sections[0].value = format!("Saved as {}", name_entry.0.trim());
+                commands.remove_resource::<NameEntry>();
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    text.sections[0].value = format!("Name: {}_", name_entry.0);
+}
+
+fn discard_name_entry(mut commands: Commands) {
+    commands.remove_resource::<NameEntry>();
+}
+
+fn handle_restart(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.just_pressed(RESTART_KEY) {
+        next_state.set(GameState::InGame);
+    }
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use super::{label, spawn_button, spawn_overlay, title, MenuAction};
+use crate::{config::SnakeConfig, game::GameState, score::HighScores};
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::HighScores), spawn_high_scores);
+    }
+}
+
+fn spawn_high_scores(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    config: Res<SnakeConfig>,
+) {
+    let entries = high_scores.entries(config.board_size(), config.boundary_mode);
+
+    let overlay = spawn_overlay(&mut commands, GameState::HighScores);
+    commands.entity(overlay).with_children(|parent| {
+        parent.spawn(title("High Scores"));
+        parent.spawn(label(format!(
+            "{}x{} {:?}",
+            config.board_size.0, config.board_size.1, config.boundary_mode
+        )));
+
+        if entries.is_empty() {
+            parent.spawn(label("No scores yet"));
+        }
+
+        for (rank, entry) in entries.iter().enumerate() {
+            parent.spawn(label(format!(
+                "{}. {}  {} (length {})",
+                rank + 1,
+                entry.name,
+                entry.score,
+                entry.length
+            )));
+        }
+
+        spawn_button(parent, "Back", MenuAction::MainMenu);
+    });
+}
@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use super::label;
+use crate::{score::Score, sim::SnakeSim, snake::Snake};
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_hud)
+            .add_systems(Update, update_hud.run_if(resource_exists::<SnakeSim>));
+    }
+}
+
+#[derive(Component)]
+struct HudText;
+
+fn spawn_hud(mut commands: Commands) {
+    commands.spawn((
+        HudText,
+        label("").with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        }),
+    ));
+}
+
+fn update_hud(
+    mut hud: Query<&mut Text, With<HudText>>,
+    players: Query<(&Snake, &Score)>,
+    sim: Res<SnakeSim>,
+) {
+    let mut players = players.iter().collect::<Vec<_>>();
+    players.sort_by_key(|(snake, _)| snake.player);
+
+    let value = players
+        .iter()
+        .map(|(snake, score)| {
+            let stats = format!(
+                "Score: {}  Length: {}",
+                score.0,
+                sim.snake(snake.player).len()
+            );
+            match players.len() {
+                1 => stats,
+                _ => format!("P{}  {}", snake.player + 1, stats),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("    ");
+
+    for mut text in hud.iter_mut() {
+        text.sections[0].value.clone_from(&value);
+    }
+}
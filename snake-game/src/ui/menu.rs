@@ -0,0 +1,24 @@
+use bevy::prelude::*;
+
+use super::{spawn_button, spawn_overlay, title, MenuAction};
+use crate::game::GameState;
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::MainMenu), spawn_main_menu);
+    }
+}
+
+fn spawn_main_menu(mut commands: Commands) {
+    let overlay = spawn_overlay(&mut commands, GameState::MainMenu);
+    commands.entity(overlay).with_children(|parent| {
+        parent.spawn(title("Snake"));
+        spawn_button(parent, "Start", MenuAction::Play);
+        spawn_button(parent, "Settings", MenuAction::Settings);
+        spawn_button(parent, "Controls", MenuAction::Controls);
+        spawn_button(parent, "High Scores", MenuAction::HighScores);
+        spawn_button(parent, "Quit", MenuAction::Quit);
+    });
+}
@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+
+use crate::game::GameState;
+
+mod controls;
+mod end_screen;
+mod high_scores;
+mod hud;
+mod menu;
+mod pause;
+mod settings;
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            controls::ControlsPlugin,
+            end_screen::EndScreenPlugin,
+            high_scores::HighScoresPlugin,
+            hud::HudPlugin,
+            menu::MenuPlugin,
+            pause::PausePlugin,
+            settings::SettingsPlugin,
+        ))
+        .add_systems(Update, (handle_menu_actions, update_button_colors));
+    }
+}
+
+const TITLE_SIZE: f32 = 24.0;
+const TEXT_SIZE: f32 = 12.0;
+
+const BUTTON_COLOR: Color = Color::srgb(0.15, 0.15, 0.15);
+const BUTTON_HOVERED_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const BUTTON_PRESSED_COLOR: Color = Color::srgb(0.35, 0.55, 0.35);
+
+/// What a menu button does when clicked.
+#[derive(Component, Clone, Copy)]
+enum MenuAction {
+    Play,
+    Resume,
+    Settings,
+    Controls,
+    HighScores,
+    MainMenu,
+    Quit,
+}
+
+fn handle_menu_actions(
+    buttons: Query<(&Interaction, &MenuAction), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    for (interaction, action) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match action {
+            MenuAction::Play | MenuAction::Resume => next_state.set(GameState::InGame),
+            MenuAction::Settings => next_state.set(GameState::Settings),
+            MenuAction::Controls => next_state.set(GameState::Controls),
+            MenuAction::HighScores => next_state.set(GameState::HighScores),
+            MenuAction::MainMenu => next_state.set(GameState::MainMenu),
+            MenuAction::Quit => {
+                app_exit.send(AppExit::Success);
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_button_colors(
+    mut buttons: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
+) {
+    for (interaction, mut background_color) in buttons.iter_mut() {
+        *background_color = match interaction {
+            Interaction::Pressed => BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => BUTTON_HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+    }
+}
+
+/// Spawns a full-window overlay that is despawned when leaving `state`.
+fn spawn_overlay(commands: &mut Commands, state: GameState) -> Entity {
+    commands
+        .spawn((
+            StateScoped(state),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+        ))
+        .id()
+}
+
+fn spawn_button(parent: &mut ChildBuilder, text: impl Into<String>, action: impl Bundle) {
+    parent
+        .spawn((
+            action,
+            ButtonBundle {
+                style: Style {
+                    min_width: Val::Px(24.0),
+                    padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                ..default()
+            },
+        ))
+        .with_children(|button| {
+            button.spawn(label(text));
+        });
+}
+
+fn title(text: impl Into<String>) -> TextBundle {
+    TextBundle::from_section(
+        text,
+        TextStyle {
+            font_size: TITLE_SIZE,
+            ..default()
+        },
+    )
+}
+
+fn label(text: impl Into<String>) -> TextBundle {
+    TextBundle::from_section(
+        text,
+        TextStyle {
+            font_size: TEXT_SIZE,
+            ..default()
+        },
+    )
+}
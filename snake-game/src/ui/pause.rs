@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+
+use super::{spawn_button, spawn_overlay, title, MenuAction};
+use crate::game::GameState;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Paused), spawn_pause_menu)
+            .add_systems(
+                Update,
+                toggle_pause
+                    .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
+            );
+    }
+}
+
+const PAUSE_KEY: KeyCode = KeyCode::Escape;
+
+fn spawn_pause_menu(mut commands: Commands) {
+    let overlay = spawn_overlay(&mut commands, GameState::Paused);
+    commands.entity(overlay).with_children(|parent| {
+        parent.spawn(title("Paused"));
+        spawn_button(parent, "Resume", MenuAction::Resume);
+        spawn_button(parent, "Main Menu", MenuAction::MainMenu);
+    });
+}
+
+fn toggle_pause(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keys.just_pressed(PAUSE_KEY) {
+        return;
+    }
+
+    match state.get() {
+        GameState::InGame => next_state.set(GameState::Paused),
+        _ => next_state.set(GameState::InGame),
+    }
+}
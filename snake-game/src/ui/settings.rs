@@ -0,0 +1,197 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use super::{label, spawn_button, spawn_overlay, title, MenuAction};
+use crate::{
+    ai::ControllerKind,
+    config::{SnakeConfig, MAX_PLAYERS},
+    game::GameState,
+    sim::BoundaryMode,
+    theme::THEMES,
+};
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Settings), spawn_settings)
+            .add_systems(
+                Update,
+                (change_settings, update_setting_values)
+                    .chain()
+                    .run_if(in_state(GameState::Settings)),
+            );
+    }
+}
+
+const BOARD_SIZE_RANGE: (u32, u32) = (5, 30);
+const TICK_RANGE: (f32, f32) = (0.05, 0.5);
+const MAX_FOOD_RANGE: (usize, usize) = (1, 10);
+const TICK_STEP: f32 = 0.05;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    Players,
+    /// Who steers the given player's snake.
+    Controller(usize),
+    BoardSize,
+    TickSpeed,
+    MaxFood,
+    BoundaryMode,
+    Interpolate,
+    Theme,
+}
+
+/// Steps a setting up or down when clicked.
+#[derive(Component)]
+struct SettingButton {
+    setting: Setting,
+    step: f32,
+}
+
+#[derive(Component)]
+struct SettingValue(Setting);
+
+fn spawn_settings(mut commands: Commands) {
+    let overlay = spawn_overlay(&mut commands, GameState::Settings);
+    commands.entity(overlay).with_children(|parent| {
+        parent.spawn(title("Settings"));
+
+        for (name, setting) in [
+            ("Players", Setting::Players),
+            ("Player 1", Setting::Controller(0)),
+            ("Player 2", Setting::Controller(1)),
+            ("Board size", Setting::BoardSize),
+            ("Tick", Setting::TickSpeed),
+            ("Food", Setting::MaxFood),
+            ("Edges", Setting::BoundaryMode),
+            ("Movement", Setting::Interpolate),
+            ("Theme", Setting::Theme),
+        ] {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(8.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn(label(name));
+                    spawn_button(
+                        row,
+                        "-",
+                        SettingButton {
+                            setting,
+                            step: -1.0,
+                        },
+                    );
+                    row.spawn((SettingValue(setting), label("")));
+                    spawn_button(row, "+", SettingButton { setting, step: 1.0 });
+                });
+        }
+
+        spawn_button(parent, "Back", MenuAction::MainMenu);
+    });
+}
+
+fn change_settings(
+    buttons: Query<(&Interaction, &SettingButton), Changed<Interaction>>,
+    mut config: ResMut<SnakeConfig>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let mut changed = config.clone();
+        match button.setting {
+            Setting::Players => {
+                changed.players = changed
+                    .players
+                    .saturating_add_signed(button.step as isize)
+                    .clamp(1, MAX_PLAYERS);
+            }
+            Setting::Controller(player) => {
+                let kinds = ControllerKind::ALL;
+                let index = kinds
+                    .iter()
+                    .position(|&kind| kind == changed.controller(player))
+                    .unwrap_or(0);
+                let index =
+                    (index as isize + button.step as isize).rem_euclid(kinds.len() as isize);
+
+                changed
+                    .controllers
+                    .resize(changed.controllers.len().max(player + 1), default());
+                changed.controllers[player] = kinds[index as usize];
+            }
+            Setting::BoardSize => {
+                let (width, height) = changed.board_size;
+                let step = |size: u32| {
+                    size.saturating_add_signed(button.step as i32)
+                        .clamp(BOARD_SIZE_RANGE.0, BOARD_SIZE_RANGE.1)
+                };
+                changed.board_size = (step(width), step(height));
+            }
+            Setting::TickSpeed => {
+                changed.tick =
+                    (changed.tick + button.step * TICK_STEP).clamp(TICK_RANGE.0, TICK_RANGE.1);
+            }
+            Setting::MaxFood => {
+                changed.max_food = changed
+                    .max_food
+                    .saturating_add_signed(button.step as isize)
+                    .clamp(MAX_FOOD_RANGE.0, MAX_FOOD_RANGE.1);
+            }
+            Setting::BoundaryMode => {
+                changed.boundary_mode = match changed.boundary_mode {
+                    BoundaryMode::Wrap => BoundaryMode::Solid,
+                    BoundaryMode::Solid => BoundaryMode::Wrap,
+                };
+            }
+            Setting::Interpolate => changed.interpolate = !changed.interpolate,
+            Setting::Theme => {
+                // A theme given with --theme is not in the list, and steps to the first one.
+                let index = THEMES
+                    .iter()
+                    .position(|&theme| theme == changed.theme)
+                    .map_or(0, |index| {
+                        (index as isize + button.step as isize).rem_euclid(THEMES.len() as isize)
+                    });
+                changed.theme = THEMES[index as usize].to_string();
+            }
+        }
+
+        // Takes effect when the next game starts.
+        match changed.validate() {
+            Ok(()) => *config = changed,
+            Err(err) => warn!("Ignoring setting change: {}", err),
+        }
+    }
+}
+
+fn update_setting_values(mut values: Query<(&SettingValue, &mut Text)>, config: Res<SnakeConfig>) {
+    for (value, mut text) in values.iter_mut() {
+        text.sections[0].value = match value.0 {
+            Setting::Players => format!("{}", config.players),
+            Setting::Controller(player) => format!("{:?}", config.controller(player)),
+            Setting::BoardSize => format!("{}x{}", config.board_size.0, config.board_size.1),
+            Setting::TickSpeed => format!("{:.2}s", config.tick),
+            Setting::MaxFood => format!("{}", config.max_food),
+            Setting::BoundaryMode => format!("{:?}", config.boundary_mode),
+            Setting::Interpolate => match config.interpolate {
+                true => "Smooth".to_string(),
+                false => "Snap".to_string(),
+            },
+            Setting::Theme => Path::new(&config.theme)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".theme.ron"))
+                .unwrap_or(&config.theme)
+                .to_string(),
+        };
+    }
+}
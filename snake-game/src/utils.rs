@@ -8,3 +8,21 @@ pub fn coords_to_translation(board_size: Vec2, grid_size: Vec2, coords: Vec2) ->
         z: 0.0,
     }
 }
+
+/// Reads `--name value` or `--name=value` from the command line.
+pub fn cli_arg(name: &str) -> Option<String> {
+    let flag = format!("--{}", name);
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+
+        if let Some(value) = arg.strip_prefix(&format!("{}=", flag)) {
+            return Some(value.to_string());
+        }
+    }
+
+    None
+}
@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+use keymap::{BindingsLabel, RebindPlugin, RebindSet, Rebindable, Rebinding, CANCEL_KEY};
+
+use crate::{
+    game::GameState,
+    player::{PlayerAction, PlayerKeymap},
+};
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RebindPlugin::<PlayerKeymap, _>::new(GameState::Controls))
+            .add_systems(OnEnter(GameState::Controls), spawn_controls)
+            .add_systems(Update, open_controls.run_if(in_state(GameState::InGame)))
+            .add_systems(
+                Update,
+                (close_controls, change_controls, update_button_colors)
+                    .chain()
+                    .before(RebindSet)
+                    .run_if(in_state(GameState::Controls)),
+            );
+    }
+}
+
+/// Opens the controls from the game, and closes them again unless a rebind is cancelled.
+const TOGGLE_KEY: KeyCode = CANCEL_KEY;
+const TITLE_SIZE: f32 = 24.0;
+const TEXT_SIZE: f32 = 12.0;
+
+const BUTTON_COLOR: Color = Color::srgb(0.15, 0.15, 0.15);
+const BUTTON_HOVERED_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const BUTTON_PRESSED_COLOR: Color = Color::srgb(0.35, 0.55, 0.35);
+
+#[derive(Component, Clone, Copy)]
+enum ControlButton {
+    Rebind(PlayerAction),
+    /// Removes every binding of the action.
+    Clear(PlayerAction),
+    /// Restores the default bindings.
+    Reset,
+    Back,
+}
+
+fn open_controls(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.just_pressed(TOGGLE_KEY) {
+        next_state.set(GameState::Controls);
+    }
+}
+
+/// Goes back to the game, unless the key is cancelling a rebind.
+fn close_controls(
+    keys: Res<ButtonInput<KeyCode>>,
+    rebinding: Option<Res<Rebinding<PlayerAction>>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if rebinding.is_none() && keys.just_pressed(TOGGLE_KEY) {
+        next_state.set(GameState::InGame);
+    }
+}
+
+fn spawn_controls(mut commands: Commands) {
+    commands
+        .spawn((
+            StateScoped(GameState::Controls),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Controls", TITLE_SIZE));
+
+            for action in PlayerAction::ALL {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(8.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn(text(format!("{:?}", action), TEXT_SIZE));
+                        row.spawn((BindingsLabel { player: 0, action }, text("", TEXT_SIZE)));
+                        spawn_button(row, "Rebind", ControlButton::Rebind(action));
+                        spawn_button(row, "Clear", ControlButton::Clear(action));
+                    });
+            }
+
+            parent.spawn(text(
+                "Press a key or gamepad button after clicking Rebind, or Escape to cancel",
+                TEXT_SIZE,
+            ));
+            spawn_button(parent, "Reset", ControlButton::Reset);
+            spawn_button(parent, "Back", ControlButton::Back);
+        });
+}
+
+fn spawn_button(parent: &mut ChildBuilder, label: &str, button: ControlButton) {
+    parent
+        .spawn((
+            button,
+            ButtonBundle {
+                style: Style {
+                    min_width: Val::Px(24.0),
+                    padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                ..default()
+            },
+        ))
+        .with_children(|button| {
+            button.spawn(text(label, TEXT_SIZE));
+        });
+}
+
+fn text(value: impl Into<String>, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size,
+            ..default()
+        },
+    )
+}
+
+fn change_controls(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &ControlButton), Changed<Interaction>>,
+    mut keymap: ResMut<PlayerKeymap>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *button {
+            ControlButton::Rebind(action) => {
+                commands.insert_resource(Rebinding { player: 0, action });
+                continue;
+            }
+            ControlButton::Back => {
+                next_state.set(GameState::InGame);
+                continue;
+            }
+            ControlButton::Clear(action) => {
+                keymap.0.clear(action);
+            }
+            ControlButton::Reset => *keymap = default(),
+        }
+
+        keymap.save();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_button_colors(
+    mut buttons: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
+) {
+    for (interaction, mut background_color) in buttons.iter_mut() {
+        *background_color = match interaction {
+            Interaction::Pressed => BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => BUTTON_HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+    }
+}
@@ -1,8 +1,8 @@
 use bevy::prelude::*;
 
 use crate::{
-    alien::AlienPlugin, player::PlayerPlugin, projectile::ProjectilePlugin,
-    resolution::ResolutionPlugin,
+    alien::AlienPlugin, controls::ControlsPlugin, player::PlayerPlugin,
+    projectile::ProjectilePlugin, resolution::ResolutionPlugin,
 };
 
 pub struct GamePlugin;
@@ -10,12 +10,14 @@ pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameState>()
+            .enable_state_scoped_entities::<GameState>()
             .add_systems(PreStartup, setup_game)
             .add_plugins((
                 ResolutionPlugin,
                 ProjectilePlugin,
                 PlayerPlugin,
                 AlienPlugin,
+                ControlsPlugin,
             ));
     }
 }
@@ -24,6 +26,8 @@ impl Plugin for GamePlugin {
 pub enum GameState {
     #[default]
     InGame,
+    /// Rebinding the ship's controls, with the game paused.
+    Controls,
     GameOver,
 }
 
@@ -1,4 +1,5 @@
 mod alien;
+mod controls;
 mod game;
 mod player;
 mod projectile;
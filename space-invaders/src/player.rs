@@ -1,6 +1,8 @@
-use std::f32::consts::PI;
+use std::{f32::consts::PI, io};
 
 use bevy::prelude::*;
+use keymap::{ActionInput, ActionMap, Binding, Rebindable};
+use serde::{Deserialize, Serialize};
 
 use crate::{game, projectile::Projectile, resolution::Resolution};
 
@@ -8,10 +10,19 @@ pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_player).add_systems(
-            Update,
-            (update_player).run_if(in_state(game::GameState::InGame)),
-        );
+        let keymap = keymap::load(KEYMAP_PATH).unwrap_or_else(|err| {
+            if err.kind() != io::ErrorKind::NotFound {
+                warn!("Could not load keymap from {}: {}", KEYMAP_PATH, err);
+            }
+            PlayerKeymap::default()
+        });
+
+        app.insert_resource(keymap)
+            .add_systems(Startup, setup_player)
+            .add_systems(
+                Update,
+                (update_player).run_if(in_state(game::GameState::InGame)),
+            );
     }
 }
 
@@ -19,6 +30,76 @@ const SPEED: f32 = 200.0;
 const BULLET_SPEED: f32 = 400.0;
 const SHOOT_COOLDOWN: f32 = 0.5;
 pub const PLAYER_SIZE: Vec2 = Vec2::new(13.0, 9.0);
+pub const KEYMAP_PATH: &str = "invaders_keymap.ron";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerAction {
+    Left,
+    Right,
+    Shoot,
+}
+
+impl PlayerAction {
+    pub const ALL: [Self; 3] = [Self::Left, Self::Right, Self::Shoot];
+}
+
+/// Bindings of the player's ship, read from [`KEYMAP_PATH`] and saved there once rebound.
+#[derive(Resource, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PlayerKeymap(pub ActionMap<PlayerAction>);
+
+/// There is a single ship, so only the first player has bindings.
+impl Rebindable for PlayerKeymap {
+    type Action = PlayerAction;
+
+    fn bindings(&self, player: usize) -> Option<&ActionMap<PlayerAction>> {
+        (player == 0).then_some(&self.0)
+    }
+
+    fn bindings_mut(&mut self, player: usize) -> Option<&mut ActionMap<PlayerAction>> {
+        (player == 0).then_some(&mut self.0)
+    }
+
+    fn save(&self) {
+        if let Err(err) = keymap::save(self, KEYMAP_PATH) {
+            error!("Could not save keymap to {}: {}", KEYMAP_PATH, err);
+        }
+    }
+}
+
+impl Default for PlayerKeymap {
+    fn default() -> Self {
+        let mut action_map = ActionMap::default();
+        action_map
+            .bind(Binding::Key(KeyCode::KeyA), PlayerAction::Left)
+            .bind(Binding::Key(KeyCode::ArrowLeft), PlayerAction::Left)
+            .bind(Binding::Key(KeyCode::KeyD), PlayerAction::Right)
+            .bind(Binding::Key(KeyCode::ArrowRight), PlayerAction::Right)
+            .bind(Binding::Key(KeyCode::Space), PlayerAction::Shoot)
+            .bind(
+                Binding::GamepadButton(GamepadButtonType::DPadLeft),
+                PlayerAction::Left,
+            )
+            .bind(
+                Binding::GamepadButton(GamepadButtonType::DPadRight),
+                PlayerAction::Right,
+            )
+            .bind(
+                Binding::GamepadAxis(GamepadAxisType::LeftStickX, false),
+                PlayerAction::Left,
+            )
+            .bind(
+                Binding::GamepadAxis(GamepadAxisType::LeftStickX, true),
+                PlayerAction::Right,
+            )
+            .bind(
+                Binding::GamepadButton(GamepadButtonType::South),
+                PlayerAction::Shoot,
+            );
+
+        Self(action_map)
+    }
+}
 
 #[derive(Component)]
 pub struct Player {
@@ -52,18 +133,21 @@ fn update_player(
     bullet_texture: Res<game::BulletTexture>,
     mut player_query: Query<(&mut Player, &mut Transform)>,
     time: Res<Time>,
-    keys: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
+    keymap: Res<PlayerKeymap>,
     resolution: Res<Resolution>,
 ) {
     let (mut player, mut transform) = player_query.single_mut();
+    let gamepads = input.gamepads();
+    let pressed = |action| input.pressed(&keymap.0, action, &gamepads);
 
     let mut horizontal = 0.0;
 
-    if keys.pressed(KeyCode::KeyA) {
+    if pressed(PlayerAction::Left) {
         horizontal -= 1.0;
     }
 
-    if keys.pressed(KeyCode::KeyD) {
+    if pressed(PlayerAction::Right) {
         horizontal += 1.0;
     }
 
@@ -81,7 +165,7 @@ fn update_player(
 
     player.shoot_timer -= time.delta_seconds();
 
-    if keys.pressed(KeyCode::Space) && player.shoot_timer <= 0.0 {
+    if pressed(PlayerAction::Shoot) && player.shoot_timer <= 0.0 {
         player.shoot_timer = SHOOT_COOLDOWN;
 
         let bullet_texture: Handle<Image> = bullet_texture.0.clone();